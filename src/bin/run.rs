@@ -4,32 +4,55 @@ use std::env;
 
 use rustymusic::opts;
 use rustymusic::raagas::sound::AudioDevice;
-use rustymusic::raagas::constants;
+use rustymusic::raagas::render::Renderer;
+use rustymusic::raagas::tuning::Tuning;
 
-use rustymusic::raagas::Melody;
+use rustymusic::raagas::{Melody, Render};
+
+fn render(melody: &Melody, config: &opts::Config, fp: &str) {
+    let mut renderer = Renderer::new(config.sample_rate, config.vol)
+        .with_instrument(config.instrument)
+        .with_drone(config.drone.clone())
+        .with_envelope(config.envelope)
+        .with_tanpura(config.tanpura)
+        .with_bpm(config.bpm)
+        .with_sa(config.sa.clone())
+        .with_tuning(Tuning::new(config.tuning));
+    melody.render(&mut renderer);
+    match renderer.write_wav(fp) {
+        Ok(_) => println!("Rendered {:.1}s of audio to {}", renderer.duration(), fp),
+        Err(e) => println!("Error writing to {}: {}", fp, e),
+    }
+}
 
 fn main() {
-    match rodio::OutputStream::try_default() {
-        Ok(out) => {
-            let (_, stream_handle) = out;
-            let opts = opts::my_opts();
-            match opts::parse(&opts, env::args().collect()) {
-                Ok(melody) => {
-                    let audio_dev = AudioDevice::new(stream_handle, constants::VOL);
-                    if let Melody::Raag(raag) = &melody {
-                        raag.play(&audio_dev)
-                    }
+    let opts = opts::my_opts();
+    match opts::parse(&opts, env::args().collect()) {
+        Ok(config) => {
+            let melody = &config.melody;
+            if let Some(fp) = &config.output {
+                render(melody, &config, fp);
+                return;
+            }
 
-                    if let Melody::SwarBlock(blk) = &melody {
-                        println!("blk : {:?}", blk);
-                        blk.play(&audio_dev)
-                    }
+            match rodio::OutputStream::try_default() {
+                Ok(out) => {
+                    let (_, stream_handle) = out;
+                    let audio_dev = AudioDevice::new(stream_handle, config.vol)
+                        .with_instrument(config.instrument)
+                        .with_drone(config.drone.clone())
+                        .with_envelope(config.envelope)
+                        .with_tanpura(config.tanpura)
+                        .with_bpm(config.bpm)
+                        .with_sa(config.sa.clone())
+                        .with_tuning(Tuning::new(config.tuning));
+                    audio_dev.play(melody);
                 },
-                Err(e) => opts::print_usage(&e.to_string(), &opts),
+                Err(e) => {
+                    println!("Error getting an output device: {}", e);
+                }
             }
         },
-        Err(e) => {
-            println!("Error getting an output device: {}", e);
-        }
+        Err(e) => opts::print_usage(&e.to_string(), &opts),
     }
 }
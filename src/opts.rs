@@ -2,12 +2,40 @@ use std::error::Error;
 
 use getopts::{Options, Matches};
 
-use crate::raagas::constants::RAAGAS;
+use crate::raagas::constants::{BPM, RAAGAS, SA, SAMPLE_RATE, TANPURA_VOL, VOL};
+use crate::raagas::sound::Hertz;
+use crate::raagas::pitch::Pitch;
+use crate::raagas::instrument::{Instrument, INSTRUMENTS};
+use crate::raagas::envelope::Envelope;
+use crate::raagas::tuning::TuningSystem;
+use crate::raagas::tanpura::{Tanpura, TanpuraTuning, TANPURA_TUNINGS};
+use crate::raagas::laya::{self, Laya, LAYAS};
 use crate::raagas::{raag, Melody, SimpleRandomiser};
 use crate::raagas::utils;
 use crate::raagas::swarblock;
 use crate::raagas::raag::raag::Raag;
 
+/// What to play, and how, as parsed from the command line
+pub struct Config {
+    pub melody: Melody,
+    pub vol: f32,
+    /// pitch of Sa
+    pub sa: Hertz,
+    pub tuning: TuningSystem,
+    pub instrument: Instrument,
+    /// swar sounded under every swar played
+    pub drone: Option<Pitch>,
+    /// replaces the instrument's own envelope
+    pub envelope: Option<Envelope>,
+    /// droning under the melody, unless turned off
+    pub tanpura: Option<Tanpura>,
+    /// tempo, in beats per minute
+    pub bpm: f32,
+    /// when set, the melody is rendered to this WAV file instead of being played
+    pub output: Option<String>,
+    pub sample_rate: u32,
+}
+
 pub fn print_usage(msg: &str, opts: &Options) {
     println!("Usage: {}", opts.usage(msg));
 }
@@ -26,6 +54,24 @@ pub fn my_opts() -> Options {
                 &format!("-r {}", supported_raagas));
     opts.optopt("c", "composition", "play composition", "composition name");
     opts.optopt("f", "play", "play swars from file", "<file>");
+    opts.optopt("s", "sa", "pitch of Sa, as a tone (e.g. D, B3) or in Hz",
+                &format!("{} (default)", SA));
+    opts.optopt("t", "tuning", "tuning system", "equal (default),just,shruti");
+    opts.optopt("i", "instrument", "instrument to play swars with",
+                &format!("-i {}", INSTRUMENTS.join(",")));
+    opts.optopt("", "drone", "swar to sound under every swar played", "<swar, e.g. M>");
+    opts.optopt("", "adsr", "envelope of each swar, in place of the instrument's",
+                "<attack,decay,sustain,release>");
+    opts.optopt("", "tanpura", "first string of the tanpura, or off",
+                &format!("{},off (default: to suit the raag)", TANPURA_TUNINGS.join(",")));
+    opts.optopt("", "tanpura-vol", "volume of the tanpura",
+                &format!("{} (default)", TANPURA_VOL));
+    opts.optopt("b", "bpm", "tempo, in beats per minute",
+                &format!("{:.0} (default)", BPM));
+    opts.optopt("l", "laya", "tempo as a laya, unless --bpm is given",
+                &format!("-l {}", LAYAS.join(",")));
+    opts.optopt("o", "output", "render to a WAV file instead of playing", "<file.wav>");
+    opts.optopt("", "rate", "sample rate of the rendered WAV file", "44100 (default)");
     opts.optflag("h", "help", "usage");
 
     opts
@@ -40,7 +86,7 @@ fn build_raag(raag: &str, composition: &str) -> Result<Raag, Box<dyn Error>> {
         );
     }
 
-    let raag = raag::load::load_yaml(raag, composition).unwrap();
+    let raag = raag::load::load_yaml(raag, composition)?;
     return Ok(raag);
 }
 
@@ -60,13 +106,112 @@ fn parse_raag(matches: &Matches) -> Option<String> {
     None
 }
 
+fn parse_vol(matches: &Matches) -> Result<f32, Box<dyn Error>> {
+    if let Some(vol) = matches.opt_str("v") {
+        return Ok(vol.parse::<f32>()?);
+    }
+
+    Ok(VOL)
+}
+
+fn parse_sa(matches: &Matches) -> Result<Hertz, Box<dyn Error>> {
+    let sa = matches.opt_str("s").unwrap_or_else(|| SA.to_string());
+
+    Ok(sa.parse::<Hertz>()?)
+}
+
+fn parse_tuning(matches: &Matches) -> Result<TuningSystem, Box<dyn Error>> {
+    if let Some(tuning) = matches.opt_str("t") {
+        return Ok(tuning.parse::<TuningSystem>()?);
+    }
+
+    Ok(TuningSystem::default())
+}
+
+fn parse_instrument(matches: &Matches) -> Result<Instrument, Box<dyn Error>> {
+    if let Some(instrument) = matches.opt_str("i") {
+        return Ok(instrument.parse::<Instrument>()?);
+    }
+
+    Ok(Instrument::default())
+}
+
+fn parse_drone(matches: &Matches) -> Result<Option<Pitch>, Box<dyn Error>> {
+    if let Some(swar) = matches.opt_str("drone") {
+        let pitch = swar.parse::<Pitch>()
+            .map_err(|_| format!("Drone {} is not a swar", swar))?;
+        return Ok(Some(pitch));
+    }
+
+    Ok(None)
+}
+
+fn parse_envelope(matches: &Matches) -> Result<Option<Envelope>, Box<dyn Error>> {
+    if let Some(envelope) = matches.opt_str("adsr") {
+        return Ok(Some(envelope.parse::<Envelope>()?));
+    }
+
+    Ok(None)
+}
+
+fn parse_tanpura(matches: &Matches) -> Result<Option<Tanpura>, Box<dyn Error>> {
+    let vol = match matches.opt_str("tanpura-vol") {
+        Some(vol) => vol.parse::<f32>()?,
+        _ => TANPURA_VOL,
+    };
+    let tuning = match matches.opt_str("tanpura") {
+        Some(t) if t.eq_ignore_ascii_case("off") => return Ok(None),
+        Some(t) => Some(t.parse::<TanpuraTuning>()?),
+        _ => None,
+    };
+
+    Ok(Some(Tanpura::new(vol).with_tuning(tuning)))
+}
+
+fn parse_bpm(matches: &Matches) -> Result<f32, Box<dyn Error>> {
+    if let Some(bpm) = matches.opt_str("b") {
+        return Ok(laya::parse_bpm(&bpm)?);
+    }
+    if let Some(laya) = matches.opt_str("l") {
+        return Ok(laya.parse::<Laya>()?.bpm());
+    }
+
+    Ok(BPM)
+}
+
+fn parse_sample_rate(matches: &Matches) -> Result<u32, Box<dyn Error>> {
+    if let Some(rate) = matches.opt_str("rate") {
+        return Ok(rate.parse::<u32>()?);
+    }
+
+    Ok(SAMPLE_RATE)
+}
+
 pub fn parse(
     opts: &Options,
     args: Vec<String>,
-) -> Result<Melody, Box<dyn Error>> {
+) -> Result<Config, Box<dyn Error>> {
     let matches = opts.parse(&args[1..])?;
-    if let Some(r) = parse_raag(&matches) {
-        if let Some(c) = parse_composition(&matches) {
+    let melody = parse_melody(&matches)?;
+
+    Ok(Config {
+        melody,
+        vol: parse_vol(&matches)?,
+        sa: parse_sa(&matches)?,
+        tuning: parse_tuning(&matches)?,
+        instrument: parse_instrument(&matches)?,
+        drone: parse_drone(&matches)?,
+        envelope: parse_envelope(&matches)?,
+        tanpura: parse_tanpura(&matches)?,
+        bpm: parse_bpm(&matches)?,
+        output: matches.opt_str("o"),
+        sample_rate: parse_sample_rate(&matches)?,
+    })
+}
+
+fn parse_melody(matches: &Matches) -> Result<Melody, Box<dyn Error>> {
+    if let Some(r) = parse_raag(matches) {
+        if let Some(c) = parse_composition(matches) {
             let raag = build_raag(r.as_str(), c.as_str())?;
             // check if play random swars flag is set
             if let Some(_) = matches.opt_str("z") {
@@ -83,9 +228,12 @@ pub fn parse(
     // playing swars from the file
     if let Some(fp) = matches.opt_str("f") {
         println!("Playing swars from the file {}", fp);
-        let lines = utils::lines_from_file(fp);
-        let s: &str = &lines.join(" ");
-        let swarblk = swarblock::SwarBlock::from(s);
+        let lines = utils::lines_from_file(fp.clone());
+        let mut swarblk = swarblock::SwarBlock(Vec::new());
+        for (i, line) in lines.iter().enumerate() {
+            swarblk.push_str(line)
+                .map_err(|e| e.with_line(&format!("{} line {}", fp, i + 1)))?;
+        }
         return Ok(Melody::SwarBlock(swarblk));
     }
 
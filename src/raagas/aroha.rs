@@ -73,12 +73,12 @@ impl Aroha {
 
     pub fn build_lower(&mut self) {
         let blks = self.build(&self.aroha.0);
-        self.lower = Some(blks.lower());
+        self.lower = blks.lower();
     }
 
     pub fn build_higher(&mut self) {
         let blks = self.build(&self.aroha.0);
-        self.higher = Some(blks.higher());
+        self.higher = blks.higher();
     }
 
     pub fn lower(&self) -> Option<&SwarBlocks> {
@@ -94,29 +94,21 @@ impl Aroha {
     }
 
     pub fn play(&self, dev: &AudioDevice) {
-        self.aroha.play(&dev);
+        dev.play(self);
     }
 
     /// Returns all three octaves of aroha
     pub fn all_octaves(&self) -> SwarBlocks {
         let mut blks = Vec::<SwarBlock>::new();
-        let mut lower_blks = self.lower().unwrap().0
-            .iter()
-            .map(|blk| blk.clone())
-            .collect::<Vec<SwarBlock>>();
-        blks.append(&mut lower_blks);
-
-        let mut aroha_blks = self.aroha().0
-            .iter()
-            .map(|blk| blk.clone())
-            .collect::<Vec<SwarBlock>>();
-        blks.append(&mut aroha_blks);
-
-        let mut higher_blks = self.higher().unwrap().0
-            .iter()
-            .map(|blk| blk.clone())
-            .collect::<Vec<SwarBlock>>();
-        blks.append(&mut higher_blks);
+        if let Some(lower) = self.lower() {
+            blks.extend(lower.0.iter().cloned());
+        }
+
+        blks.extend(self.aroha().0.iter().cloned());
+
+        if let Some(higher) = self.higher() {
+            blks.extend(higher.0.iter().cloned());
+        }
 
         SwarBlocks(blks)
     }
@@ -126,14 +118,14 @@ impl Aroha {
     /// e.g.: For swars x y z, return .x .y .z x y z x. y. z.
     pub fn swars_in_all_octaves(&self) -> Vec<&Swar> {
         let mut swars = Vec::<&Swar>::new();
-        for sw in self.lower.as_ref().unwrap().to_swars_as_ref() {
-            swars.push(sw);
+        if let Some(lower) = &self.lower {
+            swars.extend(lower.to_swars_as_ref());
         }
 
         swars.extend(self.aroha.to_swars_as_ref());
 
-        for sw in self.higher.as_ref().unwrap().to_swars_as_ref() {
-            swars.push(sw);
+        if let Some(higher) = &self.higher {
+            swars.extend(higher.to_swars_as_ref());
         }
 
         swars
@@ -141,12 +133,12 @@ impl Aroha {
 
     pub fn swars_by_context(&self, swar: &Swar) -> Option<Vec<&Swar>> {
         let all_swars = [
-            self.lower.as_ref().unwrap(),
-            &self.aroha,
-            &self.higher.as_ref().unwrap()
+            self.lower.as_ref(),
+            Some(&self.aroha),
+            self.higher.as_ref()
         ];
 
-        for blks in all_swars.iter() {
+        for blks in all_swars.iter().flatten() {
             if let Some(index) = blks.index_swar(&swar) {
                 return blks.adjacent_swars(&index);
             }
@@ -182,7 +174,7 @@ mod tests {
         let expected = ".R - .M - .P - .D -";
         let aroha = raag.aroha().build(&raag.aroha().aroha.0);
 
-        assert_eq!(aroha.lower().to_string(), expected);
+        assert_eq!(aroha.lower().unwrap().to_string(), expected);
     }
 
     /// test aroha higher octave
@@ -194,7 +186,7 @@ mod tests {
         let expected = "R. - M. - P. - D. -";
         let aroha = raag.aroha().build(&raag.aroha().aroha.0);
 
-        assert_eq!(aroha.higher().to_string(), expected);
+        assert_eq!(aroha.higher().unwrap().to_string(), expected);
     }
 
     /// test aroha in all three octaves
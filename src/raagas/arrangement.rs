@@ -0,0 +1,568 @@
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+use crate::raagas::beats::Beats;
+use crate::raagas::constants::{PLAY_PAUSE_DURATION, TIHAYI_TIMES};
+use crate::raagas::laya::Tempo;
+use crate::raagas::raag::raag::Raag;
+use crate::raagas::swarblocks::SwarBlocks;
+use crate::raagas::swarmaalika::{Line, Swarmaalika};
+use crate::raagas::composition::{Alap, Composition, Form};
+
+// the times each line of the sthayi and antara is played when the whole
+// section is
+pub const LINE_TIMES: usize = 2;
+
+/// A part of a raag an arrangement plays
+#[derive(Debug, Clone, PartialEq)]
+pub enum Part {
+    Aroha,
+    Avroha,
+    Pakad,
+    Alankars,
+    Mukra,
+    Sthayi,
+    Antara,
+    /// the taans, or for a gat the todas
+    Taans,
+    Tihayi,
+    Alap,
+    Jor,
+    Jhala,
+    /// a line of the sthayi, by its label or its place (from 1)
+    SthayiLine(String),
+    /// a line of the antara, by its label or its place (from 1)
+    AntaraLine(String),
+    /// a pause of some beats
+    Pause(Beats),
+}
+
+impl Part {
+    /// Returns the number of times the part is played unless told otherwise:
+    /// a tihayi three times, anything else once
+    pub fn times(&self) -> usize {
+        match self {
+            Part::Tihayi => TIHAYI_TIMES as usize,
+            _ => 1,
+        }
+    }
+
+    // the section of the composition whose laya the part is played at
+    fn section(&self) -> Option<&str> {
+        match self {
+            Part::Sthayi => Some("sthayi"),
+            Part::Antara => Some("antara"),
+            Part::Taans => Some("taans"),
+            Part::Tihayi => Some("tihayi"),
+            Part::Alap => Some("alap"),
+            Part::Jor => Some("jor"),
+            Part::Jhala => Some("jhala"),
+            _ => None,
+        }
+    }
+
+    // whether the part is of a composition set to a taal, played over it
+    fn in_taal(&self) -> bool {
+        matches!(self, Part::Mukra | Part::Sthayi | Part::Antara | Part::Taans | Part::Tihayi |
+                       Part::SthayiLine(_) | Part::AntaraLine(_))
+    }
+}
+
+impl FromStr for Part {
+    type Err = String;
+
+    /// Returns the part named, e.g. `pakad`, `sthayi lineA`, `antara 3`,
+    /// `jhala` or `pause 4` (beats)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, arg) = match s.trim().split_once(' ') {
+            Some((name, arg)) => (name, Some(arg.trim())),
+            _ => (s.trim(), None),
+        };
+        let part = match (name, arg) {
+            ("aroha", None) => Part::Aroha,
+            ("avroha", None) => Part::Avroha,
+            ("pakad", None) => Part::Pakad,
+            ("alankars", None) => Part::Alankars,
+            ("mukra", None) => Part::Mukra,
+            ("sthayi", None) => Part::Sthayi,
+            ("antara", None) => Part::Antara,
+            ("taans", None) | ("todas", None) => Part::Taans,
+            ("tihayi", None) => Part::Tihayi,
+            ("alap", None) => Part::Alap,
+            ("jor", None) => Part::Jor,
+            ("jhala", None) => Part::Jhala,
+            ("sthayi", Some(line)) => Part::SthayiLine(line.to_string()),
+            ("antara", Some(line)) => Part::AntaraLine(line.to_string()),
+            ("pause", None) => Part::Pause(PLAY_PAUSE_DURATION),
+            ("pause", Some(beats)) => Part::Pause(beats.parse::<Beats>()?),
+            _ => return Err(format!("{:?} is not aroha, avroha, pakad, alankars, mukra, sthayi, antara, \
+                                     taans, tihayi, alap, jor, jhala, a line of the sthayi or antara, \
+                                     or a pause", s)),
+        };
+
+        Ok(part)
+    }
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Part::Aroha => write!(f, "aroha"),
+            Part::Avroha => write!(f, "avroha"),
+            Part::Pakad => write!(f, "pakad"),
+            Part::Alankars => write!(f, "alankars"),
+            Part::Mukra => write!(f, "mukra"),
+            Part::Sthayi => write!(f, "sthayi"),
+            Part::Antara => write!(f, "antara"),
+            Part::Taans => write!(f, "taans"),
+            Part::Tihayi => write!(f, "tihayi"),
+            Part::Alap => write!(f, "alap"),
+            Part::Jor => write!(f, "jor"),
+            Part::Jhala => write!(f, "jhala"),
+            Part::SthayiLine(line) => write!(f, "sthayi {}", line),
+            Part::AntaraLine(line) => write!(f, "antara {}", line),
+            Part::Pause(beats) => write!(f, "pause {}", beats),
+        }
+    }
+}
+
+/// A step of an arrangement: a part, the times it is played and the tempo
+/// it is played at, if not that of the laya of its section
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub part: Part,
+    pub times: usize,
+    pub tempo: Option<Tempo>,
+}
+
+impl Step {
+    pub fn new(part: Part) -> Self {
+        Step {
+            times: part.times(),
+            part,
+            tempo: None,
+        }
+    }
+
+    pub fn with_times(mut self, times: usize) -> Self {
+        self.times = times;
+        self
+    }
+
+    pub fn with_tempo(mut self, tempo: Tempo) -> Self {
+        self.tempo = Some(tempo);
+        self
+    }
+}
+
+/// The parts of a raag an arrangement plays from
+#[derive(Debug, Clone, Copy)]
+pub struct Parts<'a> {
+    pub aroha: Option<&'a SwarBlocks>,
+    pub avroha: Option<&'a SwarBlocks>,
+    pub pakad: Option<&'a SwarBlocks>,
+    pub alankars: Option<&'a SwarBlocks>,
+    pub swarmaalika: Option<&'a Swarmaalika>,
+    pub alap: Option<&'a Alap>,
+}
+
+impl<'a> Parts<'a> {
+    // the lines of the composition `part` plays from
+    fn lines(&self, part: &Part) -> &'a [Line] {
+        match (part, self.swarmaalika, self.alap) {
+            (Part::Sthayi | Part::SthayiLine(_), Some(s), _) => &s.sthayi.lines,
+            (Part::Antara | Part::AntaraLine(_), Some(s), _) => &s.antara.lines,
+            (Part::Taans, Some(s), _) => &s.taans,
+            (Part::Alap, _, Some(alap)) => &alap.alap,
+            (Part::Jor, _, Some(alap)) => &alap.jor,
+            (Part::Jhala, _, Some(alap)) => &alap.jhala,
+            _ => &[],
+        }
+    }
+
+    // the tempo `section` of the composition is played at, if it has one of
+    // its own
+    fn tempo(&self, section: &str) -> Option<Tempo> {
+        match (self.swarmaalika, self.alap) {
+            (Some(swarmaalika), _) => swarmaalika.tempo(section).copied(),
+            (_, Some(alap)) => alap.tempo(section).copied(),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> From<&'a Raag> for Parts<'a> {
+    fn from(raag: &'a Raag) -> Self {
+        Parts {
+            aroha: Some(raag.aroha().aroha()),
+            avroha: Some(raag.avroha().avroha()),
+            pakad: raag.pakad().as_ref(),
+            alankars: raag.alankars().as_ref(),
+            ..Parts::from(raag.composition())
+        }
+    }
+}
+
+impl<'a> From<&'a Composition> for Parts<'a> {
+    fn from(composition: &'a Composition) -> Self {
+        Parts {
+            aroha: None,
+            avroha: None,
+            pakad: None,
+            alankars: None,
+            swarmaalika: composition.swarmaalika(),
+            alap: composition.alap(),
+        }
+    }
+}
+
+impl<'a> From<&'a Swarmaalika> for Parts<'a> {
+    fn from(swarmaalika: &'a Swarmaalika) -> Self {
+        Parts {
+            aroha: None,
+            avroha: None,
+            pakad: None,
+            alankars: None,
+            swarmaalika: Some(swarmaalika),
+            alap: None,
+        }
+    }
+}
+
+/// What is played, in order, for an arrangement
+#[derive(Debug, Clone)]
+pub enum Cue<'a> {
+    /// a heading shown as it is reached
+    Mark(String),
+    /// the tempo from here, changing (if it does) over the beats given
+    Tempo(Tempo, Beats),
+    /// swarblocks played once
+    Play(&'a SwarBlocks),
+    /// a pause of some beats
+    Pause(Beats),
+}
+
+/// The cues of an arrangement, with each run of those of the composition
+/// played over its taal and the matra of the taal the first of them falls on
+#[derive(Debug, Clone)]
+pub struct Plan<'a> {
+    pub cues: Vec<Cue<'a>>,
+    pub swarmaalika: Vec<(Range<usize>, Option<usize>)>,
+}
+
+/// The parts of a raag played, in order, e.g.:
+///
+/// arrangement:
+///   - aroha
+///   - pause
+///   - mukra
+///   - sthayi: 1
+///   - sthayi lineA
+///   - antara: { times: 2, tempo: 90-120 }
+///   - tihayi
+#[derive(Debug, Clone, PartialEq)]
+pub struct Arrangement(pub Vec<Step>);
+
+impl Default for Arrangement {
+    /// The raag with a swarmaalika
+    fn default() -> Self {
+        Arrangement::raag(Form::Swarmaalika)
+    }
+}
+
+// returns the line of `lines` labelled `name`, or at place `name` (from 1),
+// with its index
+fn find_line<'a>(lines: &'a [Line], name: &str) -> Option<(usize, &'a Line)> {
+    lines.iter()
+        .enumerate()
+        .find(|(_, line)| line.label() == Some(name))
+        .or_else(|| {
+            let i = name.parse::<usize>().ok()?.checked_sub(1)?;
+            lines.get(i).map(|line| (i, line))
+        })
+}
+
+impl Arrangement {
+    /// The aroha, avroha and pakad with a pause after each, the composition
+    /// in `form` and a pause to end on
+    pub fn raag(form: Form) -> Self {
+        let mut steps = Vec::new();
+        for part in [Part::Aroha, Part::Avroha, Part::Pakad] {
+            steps.push(Step::new(part));
+            steps.push(Step::new(Part::Pause(PLAY_PAUSE_DURATION)));
+        }
+        steps.extend(Arrangement::composition(form).0);
+        steps.push(Step::new(Part::Pause(PLAY_PAUSE_DURATION)));
+
+        Arrangement(steps)
+    }
+
+    /// A composition in `form`: the mukra, the sthayi and the antara, each
+    /// followed by the first line of the sthayi, then the taans (or todas),
+    /// also back to the sthayi, and the tihayi to end on, for the forms with
+    /// them; an alap's alap, jor and jhala in turn
+    pub fn composition(form: Form) -> Self {
+        let first = || Part::SthayiLine("1".to_string());
+        let mut parts = vec![Part::Mukra, Part::Sthayi, first(), Part::Antara, first()];
+        match form {
+            Form::Swarmaalika | Form::SargamGeet => parts.push(Part::Tihayi),
+            Form::LakshanGeet => {},
+            Form::ChhotaKhayal | Form::BadaKhayal => parts.extend(vec![Part::Taans, first()]),
+            Form::Tarana | Form::Gat => parts.extend(vec![Part::Taans, first(), Part::Tihayi]),
+            Form::Alap => parts = vec![Part::Alap, Part::Jor, Part::Jhala],
+        }
+        Arrangement(parts.into_iter().map(Step::new).collect())
+    }
+
+    /// Checks each line the arrangement plays is in the composition of
+    /// `parts`, returning the index of each step that isn't with what is wrong
+    pub fn check(&self, parts: &Parts) -> Vec<(usize, String)> {
+        let mut problems = Vec::new();
+        for (i, step) in self.0.iter().enumerate() {
+            let missing = match &step.part {
+                Part::SthayiLine(line) | Part::AntaraLine(line) => find_line(parts.lines(&step.part), line).is_none(),
+                _ => false,
+            };
+            if missing {
+                problems.push((i, format!("no line {}", step.part)));
+            }
+        }
+
+        problems
+    }
+
+    /// Returns the cues to play the arrangement from `parts`; a part that
+    /// isn't there, e.g. a composition without a mukra, is passed over. A
+    /// tempo changes over the swars played after it, until the next tempo or
+    /// pause.
+    pub fn plan<'a>(&self, parts: &Parts<'a>) -> Plan<'a> {
+        let swarmaalika = parts.swarmaalika;
+        let mut cues: Vec<Cue<'a>> = Vec::new();
+        // the runs of parts played over the taal, each with the beats of
+        // mukra it leads in with
+        let mut runs: Vec<(Range<usize>, Beats)> = Vec::new();
+        let mut in_taal = false;
+        let mut leading = true;
+
+        for step in &self.0 {
+            let mut played: Vec<Cue<'a>> = Vec::new();
+            let line = |i: usize, line: &'a Line, times: usize, played: &mut Vec<Cue<'a>>| {
+                played.push(Cue::Mark(format!("line: {}", line.name(i))));
+                played.push(Cue::Mark(line.notation()));
+                played.extend(std::iter::repeat_n(Cue::Play(&line.blks), times));
+            };
+            match &step.part {
+                Part::Aroha | Part::Avroha | Part::Pakad | Part::Alankars => {
+                    let blks = match step.part {
+                        Part::Aroha => parts.aroha,
+                        Part::Avroha => parts.avroha,
+                        Part::Pakad => parts.pakad,
+                        _ => parts.alankars,
+                    };
+                    if let Some(blks) = blks {
+                        played.push(Cue::Mark(format!("\n=> playing {}  {}", step.part, blks)));
+                        played.extend(std::iter::repeat_n(Cue::Play(blks), step.times));
+                    }
+                },
+                Part::Mukra | Part::Tihayi => {
+                    let blks = match step.part {
+                        Part::Mukra => swarmaalika.and_then(|s| s.mukra.as_ref()),
+                        _ => swarmaalika.and_then(|s| s.tihayi.as_ref()),
+                    };
+                    if let Some(blks) = blks {
+                        played.push(Cue::Mark(format!("{}: {}", step.part, blks)));
+                        played.extend(std::iter::repeat_n(Cue::Play(blks), step.times));
+                    }
+                },
+                Part::Sthayi | Part::Antara | Part::Taans | Part::Alap | Part::Jor | Part::Jhala => {
+                    let lines = parts.lines(&step.part);
+                    if matches!(step.part, Part::Alap | Part::Jor | Part::Jhala) && !lines.is_empty() {
+                        played.push(Cue::Mark(format!("\n=> playing {}", step.part)));
+                    }
+                    // the lines of a sthayi or antara are each played twice,
+                    // and taans or the lines of an alap once
+                    let line_times = match step.part {
+                        Part::Sthayi | Part::Antara => LINE_TIMES,
+                        _ => 1,
+                    };
+                    for _ in 0..step.times {
+                        for (i, l) in lines.iter().enumerate() {
+                            line(i, l, line_times, &mut played);
+                        }
+                    }
+                },
+                Part::SthayiLine(name) | Part::AntaraLine(name) => {
+                    if let Some((i, l)) = find_line(parts.lines(&step.part), name) {
+                        line(i, l, step.times, &mut played);
+                    }
+                },
+                Part::Pause(beats) => played.push(Cue::Pause(*beats * step.times as i64)),
+            }
+            if played.is_empty() {
+                continue;
+            }
+
+            // a part not over the taal, e.g. a pakad between the sthayi and
+            // antara, stops it until the next that is
+            if !step.part.in_taal() {
+                in_taal = false;
+            } else {
+                if !in_taal {
+                    runs.push((cues.len()..cues.len(), Beats::ZERO));
+                    in_taal = true;
+                    leading = true;
+                }
+                if step.part == Part::Mukra && leading {
+                    runs.last_mut().unwrap().1 += beats(&played);
+                } else {
+                    leading = false;
+                }
+            }
+            let tempo = step.tempo.or_else(|| step.part.section().and_then(|s| parts.tempo(s)));
+            if let Some(tempo) = tempo {
+                cues.push(Cue::Tempo(tempo, Beats::ZERO));
+            }
+            cues.extend(played);
+            if in_taal {
+                runs.last_mut().unwrap().0.end = cues.len();
+            }
+        }
+
+        let mut ramp: Option<usize> = None;
+        for i in 0..cues.len() {
+            match cues[i] {
+                Cue::Tempo(..) => ramp = Some(i),
+                Cue::Pause(_) => ramp = None,
+                Cue::Play(blks) => {
+                    if let Some(Cue::Tempo(_, over)) = ramp.map(|r| &mut cues[r]) {
+                        *over += blks.beats();
+                    }
+                },
+                Cue::Mark(_) => {},
+            }
+        }
+
+        // the taal starts on a whole matra, so a mukra leading in mid-matra
+        // is held back until the matra is over
+        let taal = swarmaalika.and_then(|s| Some((s.taal()?, s.matra(1)?)));
+        let mut inserted = 0;
+        let swarmaalika = runs.into_iter()
+            .map(|(range, lead)| {
+                let mut range = range.start + inserted..range.end + inserted;
+                let (taal, from) = match taal {
+                    Some(taal) => taal,
+                    None => return (range, None),
+                };
+                let whole = if lead.is_whole() { lead } else { Beats::whole(lead.floor() + 1) };
+                if whole > lead {
+                    cues.insert(range.start, Cue::Pause(whole - lead));
+                    range.end += 1;
+                    inserted += 1;
+                }
+                let matras = taal.matras() as i64;
+                (range, Some((from as i64 - 1 - whole.floor()).rem_euclid(matras) as usize + 1))
+            })
+            .collect();
+
+        Plan { cues, swarmaalika }
+    }
+}
+
+// the beats the cues play for
+fn beats(cues: &[Cue]) -> Beats {
+    cues.iter()
+        .map(|cue| match cue {
+            Cue::Play(blks) => blks.beats(),
+            Cue::Pause(beats) => *beats,
+            _ => Beats::ZERO,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::raagas::arrangement::{Arrangement, Cue, Part, Parts, Step};
+    use crate::raagas::beats::Beats;
+    use crate::raagas::laya::Tempo;
+    use crate::raagas::raag::load;
+
+    /// test parts are read by name, lines by label or place
+    #[test]
+    fn test_part_from_str() {
+        assert_eq!("pakad".parse::<Part>(), Ok(Part::Pakad));
+        assert_eq!("sthayi lineA".parse::<Part>(), Ok(Part::SthayiLine("lineA".to_string())));
+        assert_eq!("antara 3".parse::<Part>().unwrap().to_string(), "antara 3");
+        assert_eq!("pause 3/2".parse::<Part>(), Ok(Part::Pause(Beats::new(3, 2))));
+        assert!("thumri".parse::<Part>().is_err());
+        assert_eq!(Step::new(Part::Tihayi).times, 3);
+    }
+
+    /// test the default arrangement plays the mukra before the sthayi and
+    /// ends on the tihayi, over the taal from the matra the mukra starts on
+    #[test]
+    fn test_plan_default() {
+        let raag = load::load_yaml("yaman", "yeri_aali").unwrap();
+        let plan = Arrangement::default().plan(&Parts::from(&raag));
+        let marks: Vec<&str> = plan.cues.iter()
+            .filter_map(|cue| match cue {
+                Cue::Mark(m) if !m.starts_with("  ") => Some(m.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert!(marks[0].starts_with("\n=> playing aroha"));
+        assert_eq!(marks[3], "mukra: G M'");
+        assert_eq!(&marks[4..], ["line: lineA", "line: lineB", "line: lineA", "line: lineC",
+                                 "line: lineD", "line: lineA", "tihayi: N D P - - R - S"]);
+        // sam is on the 9th beat, so the sthayi starts on matra 9 and the two
+        // beats of mukra on matra 7
+        assert_eq!(plan.swarmaalika.len(), 1);
+        let (in_taal, from) = plan.swarmaalika[0].clone();
+        assert_eq!(from, Some(7));
+        assert!(matches!(plan.cues[in_taal.start], Cue::Mark(ref m) if m == "mukra: G M'"));
+        assert!(matches!(plan.cues[in_taal.end], Cue::Pause(_)));
+    }
+
+    /// test a part not over the taal between two that are, e.g. a pakad
+    /// between the sthayi and antara, stops the taal until the antara
+    #[test]
+    fn test_plan_taal_runs() {
+        let raag = load::load_yaml("yaman", "yeri_aali").unwrap();
+        let arrangement = Arrangement(vec![
+            Step::new(Part::Sthayi),
+            Step::new(Part::Pakad),
+            Step::new(Part::Antara),
+        ]);
+        let plan = arrangement.plan(&Parts::from(&raag));
+        let pakad = raag.pakad().as_ref().unwrap();
+        let pakad = plan.cues.iter()
+            .position(|cue| matches!(cue, Cue::Play(blks) if std::ptr::eq(*blks, pakad)))
+            .unwrap();
+        assert_eq!(plan.swarmaalika.len(), 2);
+        let (sthayi, from) = plan.swarmaalika[0].clone();
+        let (antara, _) = plan.swarmaalika[1].clone();
+        assert_eq!(from, Some(9));
+        assert!(sthayi.end <= pakad && pakad < antara.start);
+        assert_eq!(antara.end, plan.cues.len());
+    }
+
+    /// test a tempo changes over the swars played until the next tempo, and
+    /// lines are checked against the swarmaalika
+    #[test]
+    fn test_plan_tempo() {
+        let raag = load::load_yaml("durga", "durga").unwrap();
+        let arrangement = Arrangement(vec![
+            Step::new(Part::Sthayi).with_tempo(Tempo::ramp(60.0, 120.0)),
+            Step::new(Part::SthayiLine("lineA".to_string())).with_times(2),
+            Step::new(Part::Pause(Beats::ONE)),
+            Step::new(Part::AntaraLine("lineF".to_string())),
+        ]);
+        let swarmaalika = raag.swarmaalika().unwrap();
+        let parts = Parts::from(swarmaalika);
+        let plan = arrangement.plan(&parts);
+        let line_a = swarmaalika.sthayi.line("lineA").unwrap().blks.beats();
+        let ramp = swarmaalika.sthayi.beats() + line_a * 2;
+        assert!(matches!(plan.cues[0], Cue::Tempo(_, beats) if beats == ramp));
+        assert_eq!(arrangement.check(&parts), vec![(3, "no line antara lineF".to_string())]);
+    }
+}
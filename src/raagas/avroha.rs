@@ -74,12 +74,12 @@ impl Avroha {
 
     pub fn build_lower(&mut self) {
         let blks = self.build(&self.avroha.0);
-        self.lower = Some(blks.lower());
+        self.lower = blks.lower();
     }
 
     pub fn build_higher(&mut self) {
         let blks = self.build(&self.avroha.0);
-        self.higher = Some(blks.higher());
+        self.higher = blks.higher();
     }
 
     pub fn lower(&self) -> Option<&SwarBlocks> {
@@ -97,23 +97,15 @@ impl Avroha {
     /// Returns all three octaves of avroha
     pub fn all_octaves(&self) -> SwarBlocks {
         let mut blks = Vec::<SwarBlock>::new();
-        let mut higher_blks = self.higher().unwrap().0
-            .iter()
-            .map(|blk| blk.clone())
-            .collect::<Vec<SwarBlock>>();
-        blks.append(&mut higher_blks);
-
-        let mut avroha_blks = self.avroha().0
-            .iter()
-            .map(|blk| blk.clone())
-            .collect::<Vec<SwarBlock>>();
-        blks.append(&mut avroha_blks);
-
-        let mut lower_blks = self.lower().unwrap().0
-            .iter()
-            .map(|blk| blk.clone())
-            .collect::<Vec<SwarBlock>>();
-        blks.append(&mut lower_blks);
+        if let Some(higher) = self.higher() {
+            blks.extend(higher.0.iter().cloned());
+        }
+
+        blks.extend(self.avroha().0.iter().cloned());
+
+        if let Some(lower) = self.lower() {
+            blks.extend(lower.0.iter().cloned());
+        }
 
         SwarBlocks(blks)
     }
@@ -122,14 +114,14 @@ impl Avroha {
     /// e.g.: For swars x y z, return x. y. z. x y z .x .y .z
     pub fn swars_in_all_octaves(&self) -> Vec<&Swar> {
         let mut swars = Vec::<&Swar>::new();
-        for sw in self.higher.as_ref().unwrap().to_swars_as_ref() {
-            swars.push(sw);
+        if let Some(higher) = &self.higher {
+            swars.extend(higher.to_swars_as_ref());
         }
 
         swars.extend(self.avroha.to_swars_as_ref());
 
-        for sw in self.lower.as_ref().unwrap().to_swars_as_ref() {
-            swars.push(sw);
+        if let Some(lower) = &self.lower {
+            swars.extend(lower.to_swars_as_ref());
         }
 
         swars
@@ -137,12 +129,12 @@ impl Avroha {
 
     pub fn swars_by_context(&self, swar: &Swar) -> Option<Vec<&Swar>> {
         let all_swars = [
-            self.higher.as_ref().unwrap(),
-            &self.avroha,
-            &self.lower.as_ref().unwrap()
+            self.higher.as_ref(),
+            Some(&self.avroha),
+            self.lower.as_ref()
         ];
 
-        for blks in all_swars.iter() {
+        for blks in all_swars.iter().flatten() {
             if let Some(index) = blks.index_swar(&swar) {
                 return blks.adjacent_swars(&index);
             }
@@ -152,7 +144,7 @@ impl Avroha {
     }
 
     pub fn play(&self, dev: &AudioDevice) {
-        self.avroha.play(&dev);
+        dev.play(self);
     }
 }
 
@@ -182,7 +174,7 @@ mod tests {
         let expected = ".D - .P - .M - .R -";
         let avroha = raag.avroha().build(&raag.avroha().avroha.0);
 
-        assert_eq!(avroha.lower().to_string(), expected);
+        assert_eq!(avroha.lower().unwrap().to_string(), expected);
     }
 
     /// test avvroha higher octave
@@ -194,7 +186,7 @@ mod tests {
         let expected = "D. - P. - M. - R. -";
         let avroha = raag.avroha().build(&raag.avroha().avroha.0);
 
-        assert_eq!(avroha.higher().to_string(), expected);
+        assert_eq!(avroha.higher().unwrap().to_string(), expected);
     }
 
     /// test avroha in all three octaves
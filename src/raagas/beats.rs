@@ -0,0 +1,223 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Div, Mul, Sub, SubAssign};
+use std::str::FromStr;
+
+/// A length of time in beats (matras), kept as an exact fraction so that
+/// swars split into thirds, kan swars and swars held over from beat to beat
+/// always add up to whole beats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Beats {
+    num: i64,
+    den: i64,
+}
+
+const fn gcd(a: i64, b: i64) -> i64 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    a
+}
+
+impl Beats {
+    pub const ZERO: Beats = Beats::new(0, 1);
+    pub const ONE: Beats = Beats::new(1, 1);
+
+    /// Returns `num / den` beats, in its lowest terms
+    pub const fn new(num: i64, den: i64) -> Self {
+        assert!(den != 0, "a length in beats can't be divided by zero");
+        let g = match gcd(num, den) {
+            0 => 1,
+            g => g,
+        };
+        let sign = if den < 0 { -1 } else { 1 };
+        Beats { num: sign * num / g, den: sign * den / g }
+    }
+
+    /// Returns `n` whole beats
+    pub const fn whole(n: i64) -> Self {
+        Beats { num: n, den: 1 }
+    }
+
+    pub fn numer(&self) -> i64 {
+        self.num
+    }
+
+    pub fn denom(&self) -> i64 {
+        self.den
+    }
+
+    pub fn is_whole(&self) -> bool {
+        self.den == 1
+    }
+
+    /// Returns the number of whole beats, rounding down
+    pub fn floor(&self) -> i64 {
+        self.num.div_euclid(self.den)
+    }
+
+    /// Returns the number of `1 / division` parts of a beat the length takes
+    /// up, if it is a whole number of them
+    pub fn parts(&self, division: usize) -> Option<usize> {
+        let parts = *self * division as i64;
+        if parts.is_whole() && parts.num >= 0 {
+            Some(parts.num as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the fewest equal parts a beat can be split into for each of
+    /// `lengths` to take up a whole number of them, e.g. 6 for halves and
+    /// thirds
+    pub fn division<I: IntoIterator<Item = Beats>>(lengths: I) -> usize {
+        lengths.into_iter().fold(1, |d, b| d / gcd(d, b.den) * b.den) as usize
+    }
+
+    /// Returns the length as a number of beats, for timing in seconds
+    pub fn as_f32(&self) -> f32 {
+        self.num as f32 / self.den as f32
+    }
+}
+
+impl Default for Beats {
+    fn default() -> Self {
+        Beats::ZERO
+    }
+}
+
+impl From<usize> for Beats {
+    fn from(n: usize) -> Self {
+        Beats::whole(n as i64)
+    }
+}
+
+impl Add for Beats {
+    type Output = Beats;
+
+    fn add(self, other: Beats) -> Beats {
+        Beats::new(self.num * other.den + other.num * self.den, self.den * other.den)
+    }
+}
+
+impl Sub for Beats {
+    type Output = Beats;
+
+    fn sub(self, other: Beats) -> Beats {
+        Beats::new(self.num * other.den - other.num * self.den, self.den * other.den)
+    }
+}
+
+impl Mul<i64> for Beats {
+    type Output = Beats;
+
+    fn mul(self, n: i64) -> Beats {
+        Beats::new(self.num * n, self.den)
+    }
+}
+
+impl Div<i64> for Beats {
+    type Output = Beats;
+
+    // dividing the length multiplies its denominator
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, n: i64) -> Beats {
+        Beats::new(self.num, self.den * n)
+    }
+}
+
+impl AddAssign for Beats {
+    fn add_assign(&mut self, other: Beats) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Beats {
+    fn sub_assign(&mut self, other: Beats) {
+        *self = *self - other;
+    }
+}
+
+impl Sum for Beats {
+    fn sum<I: Iterator<Item = Beats>>(iter: I) -> Beats {
+        iter.fold(Beats::ZERO, |total, b| total + b)
+    }
+}
+
+impl Ord for Beats {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.num * other.den).cmp(&(other.num * self.den))
+    }
+}
+
+impl PartialOrd for Beats {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl FromStr for Beats {
+    type Err = String;
+
+    /// Returns the beats written as a whole number or a fraction, e.g. `2`
+    /// or `3/2`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |_| format!("{} is not a number of beats", s);
+        match s.split_once('/') {
+            Some((num, den)) => {
+                let den = den.trim().parse::<i64>().map_err(invalid)?;
+                if den == 0 {
+                    return Err(format!("{} is not a number of beats", s));
+                }
+                Ok(Beats::new(num.trim().parse::<i64>().map_err(invalid)?, den))
+            },
+            _ => Ok(Beats::whole(s.trim().parse::<i64>().map_err(invalid)?)),
+        }
+    }
+}
+
+impl fmt::Display for Beats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.den {
+            1 => write!(f, "{}", self.num),
+            den => write!(f, "{}/{}", self.num, den),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::raagas::beats::Beats;
+
+    /// test thirds and fifths of a beat add up to whole beats exactly
+    #[test]
+    fn test_beats_arithmetic() {
+        let third = Beats::new(1, 3);
+        assert_eq!(third + third + third, Beats::ONE);
+        let kan = Beats::new(1, 5);
+        assert_eq!(kan + (Beats::ONE - kan), Beats::ONE);
+        let line: Beats = std::iter::repeat_n(Beats::new(1, 6), 6).sum();
+        assert_eq!(line, Beats::ONE);
+        assert_eq!(Beats::new(2, 4), Beats::new(1, 2));
+        assert_eq!(Beats::new(3, -6), Beats::new(-1, 2));
+        assert!(Beats::new(2, 3) < Beats::new(3, 4));
+        assert_eq!((Beats::new(5, 3) * 3).floor(), 5);
+        assert_eq!(Beats::new(3, 2) / 3, Beats::new(1, 2));
+    }
+
+    /// test lengths are shown and read as whole numbers or fractions
+    #[test]
+    fn test_beats_display() {
+        assert_eq!(Beats::new(6, 4).to_string(), "3/2");
+        assert_eq!(Beats::whole(2).to_string(), "2");
+        assert_eq!("3/2".parse::<Beats>(), Ok(Beats::new(3, 2)));
+        assert!("1/0".parse::<Beats>().is_err());
+        assert_eq!(Beats::new(2, 3).parts(6), Some(4));
+        assert_eq!(Beats::new(1, 5).parts(2), None);
+        assert_eq!(Beats::division(vec![Beats::new(1, 2), Beats::new(2, 3), Beats::ONE]), 6);
+    }
+}
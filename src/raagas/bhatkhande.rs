@@ -0,0 +1,96 @@
+use crate::raagas::taal::Taal;
+use crate::raagas::SwarBlocks;
+
+// separates the vibhags of a row
+const BAR: &str = " | ";
+
+/// Lays out notation as in Bhatkhande's notation: a row for each avartan
+/// (cycle) of the taal with `|` between its vibhags, and under each vibhag its
+/// mark, `X` for sam, `0` for a khali or the number of a tali
+#[derive(Debug, Clone)]
+pub struct Bhatkhande<'a> {
+    taal: &'a Taal,
+    // the matra of the taal the first swarbeat falls on
+    from: usize,
+}
+
+impl<'a> Bhatkhande<'a> {
+    pub fn new(taal: &'a Taal) -> Self {
+        Bhatkhande { taal, from: 1 }
+    }
+
+    /// Returns the layout starting on `matra` of the taal, the matras of the
+    /// first row before it left blank
+    pub fn with_from(mut self, matra: usize) -> Self {
+        self.from = self.taal.matra(matra);
+        self
+    }
+
+    /// Returns the swarbeats of `blks` laid out over the taal
+    pub fn swarblocks(&self, blks: &SwarBlocks) -> String {
+        self.layout(&blks.swarbeats())
+    }
+
+    /// Returns `beats` laid out over the taal, a beat to every matra
+    pub fn layout<T: ToString>(&self, beats: &[T]) -> String {
+        let mut cells: Vec<String> = vec![String::new(); self.from - 1];
+        cells.extend(beats.iter().map(|b| b.to_string()));
+        let width = cells.iter().map(|c| c.chars().count()).max().unwrap_or(0).max(1);
+        let marks = self.taal.marks();
+
+        let mut rows = Vec::new();
+        for avartan in cells.chunks(self.taal.matras()) {
+            let mut notation = Vec::new();
+            let mut under = Vec::new();
+            let mut at = 0;
+            for (vibhag, mark) in self.taal.vibhags().iter().zip(&marks) {
+                if at >= avartan.len() {
+                    break;
+                }
+                let matras = &avartan[at..avartan.len().min(at + vibhag.matras())];
+                let vibhag_width = vibhag.matras() * (width + 1) - 1;
+                let padded: Vec<String> = matras.iter()
+                    .map(|c| format!("{:<width$}", c, width = width))
+                    .collect();
+                notation.push(format!("{:<width$}", padded.join(" "), width = vibhag_width));
+                under.push(format!("{:<width$}", mark, width = vibhag_width));
+                at += vibhag.matras();
+            }
+            rows.push(notation.join(BAR).trim_end().to_string());
+            rows.push(under.join(&" ".repeat(BAR.len())).trim_end().to_string());
+        }
+
+        rows.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::raagas::bhatkhande::Bhatkhande;
+    use crate::raagas::taal::Taal;
+
+    /// test a line is laid out an avartan to a row, with vibhags barred and
+    /// marked
+    #[test]
+    fn test_layout() {
+        let dadra = Taal::load("dadra").unwrap();
+        let beats = ["S", "R", "G", "M", "P", "D", "N", "S."];
+        let rows = Bhatkhande::new(&dadra).layout(&beats);
+        assert_eq!(rows, "S  R  G  | M  P  D\nX          0\nN  S.\nX");
+    }
+
+    /// test a line starting after sam leaves the matras before it blank
+    #[test]
+    fn test_layout_from_khali() {
+        let teentaal = Taal::load("teentaal").unwrap();
+        let beats = ["N", "D", "P", "-", "-", "R", "-", "S", "G"];
+        let rows = Bhatkhande::new(&teentaal).with_from(9).layout(&beats);
+        let expected = [
+            "        |         | N D P - | - R - S",
+            "X         2         0         3",
+            "G",
+            "X",
+        ];
+        assert_eq!(rows, expected.join("\n"));
+    }
+}
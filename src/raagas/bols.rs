@@ -0,0 +1,114 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::raagas::swarbeat::SwarBeat;
+use crate::raagas::SwarBlocks;
+
+// the syllable sung on over a swarbeat held from the one before, or sung on
+// across the swars of a beat
+pub const HELD: &str = "-";
+
+/// The bols (syllables of the lyrics) of a line of a bandish, one for each
+/// swarbeat of the line, e.g. `e ri aa - - li` against `N D P - - R`; a `-`
+/// carries on the syllable before it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bols(pub Vec<String>);
+
+impl Bols {
+    /// Checks the bols can be sung against the swarbeats of `blks`: there is a
+    /// syllable for each swarbeat, and a new syllable only starts on a
+    /// swarbeat with a swar starting on it
+    pub fn check(&self, blks: &SwarBlocks) -> Result<(), String> {
+        let swarbeats = blks.swarbeats();
+        if self.0.len() != swarbeats.len() {
+            return Err(format!("{} syllables for {} swarbeats", self.0.len(), swarbeats.len()));
+        }
+        if self.0.first().is_some_and(|bol| bol == HELD) {
+            return Err("the first syllable can't carry on from the one before".to_string());
+        }
+        for (i, (sw_bt, bol)) in swarbeats.iter().zip(&self.0).enumerate() {
+            if bol != HELD && !sw_bt.swars.iter().any(|sw| sw.pitch.is_some()) {
+                return Err(format!("syllable {:?} at swarbeat {} has no swar to be sung on", bol, i + 1));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns each swarbeat of `blks` with the syllable sung on it
+    pub fn pairs<'a>(&'a self, blks: &'a SwarBlocks) -> Vec<(&'a SwarBeat, &'a str)> {
+        blks.swarbeats()
+            .into_iter()
+            .zip(self.0.iter().map(|bol| bol.as_str()))
+            .collect()
+    }
+
+    /// Returns the notation of `blks` with the bols written under it, each
+    /// syllable below its swarbeat
+    pub fn align(&self, blks: &SwarBlocks) -> String {
+        let mut swars = Vec::new();
+        let mut bols = Vec::new();
+        for (sw_bt, bol) in self.pairs(blks) {
+            let sw_bt = sw_bt.to_string();
+            let width = sw_bt.chars().count().max(bol.chars().count());
+            swars.push(format!("{:<width$}", sw_bt, width = width));
+            bols.push(format!("{:<width$}", bol, width = width));
+        }
+
+        format!("{}\n{}", swars.join(" ").trim_end(), bols.join(" ").trim_end())
+    }
+}
+
+impl FromStr for Bols {
+    type Err = String;
+
+    /// Returns the bols of a line, its syllables separated by spaces
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bols: Vec<String> = s.split_whitespace().map(|bol| bol.to_string()).collect();
+        if bols.is_empty() {
+            return Err("no syllables".to_string());
+        }
+
+        Ok(Bols(bols))
+    }
+}
+
+impl fmt::Display for Bols {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::raagas::bols::Bols;
+    use crate::raagas::swarblock::SwarBlock;
+    use crate::raagas::swarblocks::SwarBlocks;
+
+    fn line(s: &str) -> SwarBlocks {
+        SwarBlocks(vec![SwarBlock::from(s)])
+    }
+
+    /// test bols are checked against the swarbeats they are sung on
+    #[test]
+    fn test_bols_check() {
+        let blks = line("N D P - - R:G :S");
+        assert!("e ri aa - - li na".parse::<Bols>().unwrap().check(&blks).is_ok());
+        assert!("e ri aa - li".parse::<Bols>().unwrap().check(&blks).is_err());
+        // a new syllable can't start on a held swarbeat
+        let e = "e ri aa li - na pi".parse::<Bols>().unwrap().check(&blks).unwrap_err();
+        assert_eq!(e, "syllable \"li\" at swarbeat 4 has no swar to be sung on");
+        assert!("- ri aa - - li na".parse::<Bols>().unwrap().check(&blks).is_err());
+        assert!("".parse::<Bols>().is_err());
+    }
+
+    /// test each syllable is written below its swarbeat
+    #[test]
+    fn test_bols_align() {
+        let blks = line("N D P - R:G");
+        let bols: Bols = "e ri aali - na".parse().unwrap();
+        assert_eq!(bols.align(&blks), "N D  P    - R:G\ne ri aali - na");
+        assert_eq!(bols.pairs(&blks)[4].1, "na");
+        assert_eq!(bols.pairs(&blks)[4].0.to_string(), "R:G");
+    }
+}
@@ -0,0 +1,276 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::raagas::sound::AudioDevice;
+use crate::raagas::swarmaalika::{Line, Swarmaalika};
+use crate::raagas::laya::{Laya, Tempo, TempoMap};
+use crate::raagas::taal::Taal;
+
+pub const FORMS: [&str; 8] = [
+    "swarmaalika", "sargam geet", "lakshan geet", "chhota khayal", "bada khayal", "tarana", "gat", "alap"
+];
+
+/// The form of a composition, which decides the sections it is made of and
+/// how it is played
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Form {
+    #[default]
+    Swarmaalika,
+    SargamGeet,
+    LakshanGeet,
+    ChhotaKhayal,
+    BadaKhayal,
+    Tarana,
+    Gat,
+    Alap,
+}
+
+impl Form {
+    /// Returns the sections a composition in the form is made of
+    pub fn sections(&self) -> &'static [&'static str] {
+        match self {
+            Form::Swarmaalika | Form::SargamGeet => &["sam", "mukra", "sthayi", "antara", "tihayi"],
+            Form::LakshanGeet => &["sam", "mukra", "sthayi", "antara"],
+            Form::ChhotaKhayal | Form::BadaKhayal => &["sam", "mukra", "sthayi", "antara", "taans"],
+            Form::Tarana => &["sam", "mukra", "sthayi", "antara", "taans", "tihayi"],
+            Form::Gat => &["sam", "mukra", "sthayi", "antara", "todas", "tihayi"],
+            Form::Alap => &["alap", "jor", "jhala"],
+        }
+    }
+
+    /// Returns the laya each section of a composition in the form is played
+    /// at, unless it is given one of its own, e.g. a bada khayal is sung in
+    /// vilambit
+    pub fn layas(&self) -> &'static [(&'static str, Laya)] {
+        match self {
+            Form::BadaKhayal => &[("sthayi", Laya::Vilambit), ("antara", Laya::Vilambit), ("taans", Laya::Madhya)],
+            Form::ChhotaKhayal => &[("sthayi", Laya::Madhya), ("antara", Laya::Madhya), ("taans", Laya::Drut)],
+            Form::Tarana => &[("sthayi", Laya::Drut), ("antara", Laya::Drut), ("taans", Laya::Drut)],
+            Form::Alap => &[("alap", Laya::Vilambit), ("jor", Laya::Madhya), ("jhala", Laya::Drut)],
+            _ => &[],
+        }
+    }
+}
+
+impl FromStr for Form {
+    type Err = String;
+
+    /// Returns the form named, e.g. `chhota khayal` (or `chhota_khayal`)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace(['_', '-'], " ").as_str() {
+            "swarmaalika" => Ok(Form::Swarmaalika),
+            "sargam geet" => Ok(Form::SargamGeet),
+            "lakshan geet" => Ok(Form::LakshanGeet),
+            "chhota khayal" => Ok(Form::ChhotaKhayal),
+            "bada khayal" => Ok(Form::BadaKhayal),
+            "tarana" => Ok(Form::Tarana),
+            "gat" => Ok(Form::Gat),
+            "alap" => Ok(Form::Alap),
+            _ => Err(format!("Form {} is unsupported, forms allowed: {}", s, FORMS.join(","))),
+        }
+    }
+}
+
+impl fmt::Display for Form {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let form = match self {
+            Form::Swarmaalika => "swarmaalika",
+            Form::SargamGeet => "sargam geet",
+            Form::LakshanGeet => "lakshan geet",
+            Form::ChhotaKhayal => "chhota khayal",
+            Form::BadaKhayal => "bada khayal",
+            Form::Tarana => "tarana",
+            Form::Gat => "gat",
+            Form::Alap => "alap",
+        };
+        write!(f, "{}", form)
+    }
+}
+
+/// An alap unfolding the raag without a taal: slowly in the alap, over a
+/// steady pulse in the jor, and fast in the jhala
+#[derive(Debug, Clone)]
+pub struct Alap {
+    pub alap: Vec<Line>,
+    pub jor: Vec<Line>,
+    pub jhala: Vec<Line>,
+    tempos: TempoMap,
+}
+
+impl Alap {
+    pub fn new(alap: Vec<Line>, jor: Vec<Line>, jhala: Vec<Line>) -> Self {
+        Alap {
+            alap,
+            jor,
+            jhala,
+            tempos: TempoMap::new(),
+        }
+    }
+
+    /// Returns the alap with its sections played at `tempos`
+    pub fn with_tempos(mut self, tempos: TempoMap) -> Self {
+        self.tempos = tempos;
+        self
+    }
+
+    /// Returns the tempo `section` is played at, if it has one of its own
+    pub fn tempo(&self, section: &str) -> Option<&Tempo> {
+        self.tempos.get(section)
+    }
+}
+
+/// A composition in one of its forms: all but the alap are set to a taal,
+/// each with the sections of its form
+#[derive(Debug, Clone)]
+pub enum Composition {
+    Swarmaalika(Swarmaalika),
+    SargamGeet(Swarmaalika),
+    LakshanGeet(Swarmaalika),
+    ChhotaKhayal(Swarmaalika),
+    BadaKhayal(Swarmaalika),
+    Tarana(Swarmaalika),
+    Gat(Swarmaalika),
+    Alap(Alap),
+}
+
+impl Composition {
+    /// Returns the composition of `form` with the sections of `swarmaalika`;
+    /// `None` for an alap, which isn't set to a taal
+    pub fn new(form: Form, swarmaalika: Swarmaalika) -> Option<Self> {
+        let composition = match form {
+            Form::Swarmaalika => Composition::Swarmaalika(swarmaalika),
+            Form::SargamGeet => Composition::SargamGeet(swarmaalika),
+            Form::LakshanGeet => Composition::LakshanGeet(swarmaalika),
+            Form::ChhotaKhayal => Composition::ChhotaKhayal(swarmaalika),
+            Form::BadaKhayal => Composition::BadaKhayal(swarmaalika),
+            Form::Tarana => Composition::Tarana(swarmaalika),
+            Form::Gat => Composition::Gat(swarmaalika),
+            Form::Alap => return None,
+        };
+
+        Some(composition)
+    }
+
+    pub fn form(&self) -> Form {
+        match self {
+            Composition::Swarmaalika(_) => Form::Swarmaalika,
+            Composition::SargamGeet(_) => Form::SargamGeet,
+            Composition::LakshanGeet(_) => Form::LakshanGeet,
+            Composition::ChhotaKhayal(_) => Form::ChhotaKhayal,
+            Composition::BadaKhayal(_) => Form::BadaKhayal,
+            Composition::Tarana(_) => Form::Tarana,
+            Composition::Gat(_) => Form::Gat,
+            Composition::Alap(_) => Form::Alap,
+        }
+    }
+
+    /// Returns the sections of the composition set to a taal; `None` for an
+    /// alap
+    pub fn swarmaalika(&self) -> Option<&Swarmaalika> {
+        match self {
+            Composition::Swarmaalika(s) | Composition::SargamGeet(s) | Composition::LakshanGeet(s) |
+            Composition::ChhotaKhayal(s) | Composition::BadaKhayal(s) | Composition::Tarana(s) |
+            Composition::Gat(s) => Some(s),
+            Composition::Alap(_) => None,
+        }
+    }
+
+    // returns the composition with `f` applied to its sections set to a taal
+    fn map<F: FnOnce(Swarmaalika) -> Swarmaalika>(self, f: F) -> Self {
+        match self {
+            Composition::Swarmaalika(s) => Composition::Swarmaalika(f(s)),
+            Composition::SargamGeet(s) => Composition::SargamGeet(f(s)),
+            Composition::LakshanGeet(s) => Composition::LakshanGeet(f(s)),
+            Composition::ChhotaKhayal(s) => Composition::ChhotaKhayal(f(s)),
+            Composition::BadaKhayal(s) => Composition::BadaKhayal(f(s)),
+            Composition::Tarana(s) => Composition::Tarana(f(s)),
+            Composition::Gat(s) => Composition::Gat(f(s)),
+            Composition::Alap(alap) => Composition::Alap(alap),
+        }
+    }
+
+    pub fn alap(&self) -> Option<&Alap> {
+        match self {
+            Composition::Alap(alap) => Some(alap),
+            _ => None,
+        }
+    }
+
+    /// Returns the composition set to `taal`, unless it is an alap
+    pub fn with_taal(self, taal: Option<Taal>) -> Self {
+        self.map(|s| s.with_taal(taal))
+    }
+
+    /// Returns the composition with its sections played at `tempos`, and
+    /// those without a tempo of their own at the laya of its form
+    pub fn with_tempos(self, mut tempos: TempoMap) -> Self {
+        for (section, laya) in self.form().layas() {
+            if tempos.get(section).is_none() {
+                // every section a form has a laya for can be given a tempo
+                // (see test_form_layas_are_sections)
+                tempos.insert(section, Tempo::from(*laya)).unwrap();
+            }
+        }
+        match self {
+            Composition::Alap(alap) => Composition::Alap(alap.with_tempos(tempos)),
+            composition => composition.map(|s| s.with_tempos(tempos)),
+        }
+    }
+
+    /// The beat of the composition that falls on the taal's sam
+    pub fn sam(&self) -> usize {
+        self.swarmaalika().map_or(1, |s| s.sam())
+    }
+
+    pub fn play(&self, dev: &AudioDevice) {
+        dev.play(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::raagas::composition::{Alap, Composition, Form, FORMS};
+    use crate::raagas::laya::{Laya, Tempo, TempoMap, SECTIONS};
+    use crate::raagas::raag::load;
+
+    /// test forms are read by name, with `_` or `-` between words
+    #[test]
+    fn test_form_from_str() {
+        assert_eq!("chhota khayal".parse::<Form>(), Ok(Form::ChhotaKhayal));
+        assert_eq!("Bada_Khayal".parse::<Form>(), Ok(Form::BadaKhayal));
+        assert_eq!("sargam-geet".parse::<Form>().unwrap().to_string(), "sargam geet");
+        assert!("thumri".parse::<Form>().is_err());
+    }
+
+    /// test every section a form has a laya for can be given a tempo, as
+    /// `Composition::with_tempos` relies on
+    #[test]
+    fn test_form_layas_are_sections() {
+        for form in FORMS.iter() {
+            let form = form.parse::<Form>().unwrap();
+            for (section, _) in form.layas() {
+                assert!(SECTIONS.contains(section), "{} of {} has no tempo", section, form);
+                assert!(form.sections().contains(section), "{} is not a section of {}", section, form);
+            }
+        }
+    }
+
+    /// test a composition takes the laya of its form for the sections not
+    /// given one
+    #[test]
+    fn test_form_layas() {
+        let raag = load::load_yaml("yaman", "yeri_aali").unwrap();
+        let swarmaalika = raag.swarmaalika().unwrap().clone();
+        let mut tempos = TempoMap::new();
+        tempos.insert("sthayi", Tempo::new(50.0)).unwrap();
+        let khayal = Composition::new(Form::BadaKhayal, swarmaalika).unwrap().with_tempos(tempos);
+        let bandish = khayal.swarmaalika().unwrap();
+        assert_eq!(bandish.tempo("sthayi"), Some(&Tempo::new(50.0)));
+        assert_eq!(bandish.tempo("antara"), Some(&Tempo::from(Laya::Vilambit)));
+        assert_eq!(bandish.tempo("taans"), Some(&Tempo::from(Laya::Madhya)));
+
+        let alap = Composition::Alap(Alap::new(Vec::new(), Vec::new(), Vec::new())).with_tempos(TempoMap::new());
+        assert!(alap.swarmaalika().is_none());
+        assert_eq!(alap.alap().unwrap().tempo("jhala"), Some(&Tempo::from(Laya::Drut)));
+    }
+}
@@ -1,53 +1,118 @@
 use std::collections::HashMap;
 use crate::raagas::sound::Hertz;
+use crate::raagas::pitch::Pitch;
+use crate::raagas::beats::Beats;
+use crate::raagas::tuning::Tuning;
 
 pub(crate) const RAAGAS: [&str;9] = [
     "durga", "yaman", "bhupali", "hamsadhwani",
     "yeri_aali", "bairav", "bhairavi", "daesh", "malkauns"
 ];
-pub const BPS: f32 = 0.7; // equivalent to 120 BPM
+pub const BPS: f32 = 0.7; // seconds per beat by default
+pub const BPM: f32 = 60.0 / BPS; // about 86 beats per minute
 pub const CONF_DIR: &str = "./config";
 pub const BEAT_MP3: (&str, f32) = ("./samples/1beat.mp3", BPS);
 pub const TIHAYI_TIMES: i8 = 3;
-pub const KAN_SWAR_BEAT_COUNT: f32 = 0.2;
+pub const KAN_SWAR_BEAT_COUNT: Beats = Beats::new(1, 5);
+pub const ANDOLAN_DEPTH: f32 = 30.0; // cents either side of the swar
+pub const ANDOLAN_RATE: f32 = 1.0; // oscillations per second
 pub const VOL: f32 = 0.5;
-pub const PLAY_PAUSE_DURATION: f32 = 2.0;
+pub const PLAY_PAUSE_DURATION: Beats = Beats::whole(2);
+pub const SAMPLE_RATE: u32 = 44100;
+pub const TANPURA_VOL: f32 = 0.3;
+pub const TANPURA_CYCLE: f32 = 3.2; // seconds to pluck all four strings
 
-lazy_static! {
-    pub static ref SWARS: HashMap<&'static str, Hertz> = initialise_swars();
+pub const SA: &str = "C#";
+pub const TONES: [&str; 12] = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
+// swars of a saptak and their distance (in semitones) from Sa
+pub const SAPTAK: [(&str, i32); 12] = [
+    ("S", 0), ("r", 1), ("R", 2), ("g", 3), ("G", 4), ("M", 5),
+    ("M'", 6), ("P", 7), ("d", 8), ("D", 9), ("n", 10), ("N", 11)
+];
+// the octaves swars are played in, from ati-mandra (`..S`) to ati-taar (`S..`)
+pub const LOWEST_OCTAVE: i32 = -2;
+pub const HIGHEST_OCTAVE: i32 = 2;
+
+/// Returns the frequency of Sa swars are played at, unless a renderer is
+/// given its own
+pub fn tonic() -> Hertz {
+    SA.parse::<Hertz>().unwrap()
 }
 
-pub fn initialise_swars<'a>() -> HashMap<&'a str, Hertz> {
-    let mut swars: HashMap<&str, Hertz> = HashMap::new();
-    swars.insert(".G", Hertz::new(174.61, "F".to_string()));
-    swars.insert(".M", Hertz::new(185.00, "F#".to_string()));
-    swars.insert(".M'", Hertz::new(196.00, "G".to_string()));
-    swars.insert(".P", Hertz::new(207.65, "G#".to_string()));
-    swars.insert(".d", Hertz::new(220.00, "A".to_string()));
-    swars.insert(".D", Hertz::new(233.08, "A#".to_string()));
-    swars.insert(".n", Hertz::new(246.94, "B".to_string()));
-    swars.insert(".N", Hertz::new(261.63, "C".to_string()));
-
-    swars.insert("S", Hertz::new(277.18, "C#".to_string()));
-    swars.insert("r", Hertz::new(293.66, "D".to_string()));
-    swars.insert("R", Hertz::new(311.13, "D#".to_string()));
-    swars.insert("g", Hertz::new(329.63, "E".to_string()));
-    swars.insert("G", Hertz::new(349.23, "F".to_string()));
-    swars.insert("M", Hertz::new(369.99, "F#".to_string()));
-    swars.insert("M'", Hertz::new(392.00, "G".to_string()));
-    swars.insert("P", Hertz::new(415.30, "G#".to_string()));
-    swars.insert("d", Hertz::new(440.0, "A".to_string()));
-    swars.insert("D", Hertz::new(466.16, "A#".to_string()));
-    swars.insert("n", Hertz::new(493.88, "B".to_string()));
-    swars.insert("N", Hertz::new(523.25, "C".to_string()));
-    swars.insert("S.", Hertz::new(554.37, "C#".to_string()));
-    swars.insert("r.", Hertz::new(587.33, "D".to_string()));
-    swars.insert("R.", Hertz::new(622.25, "D#".to_string()));
-    swars.insert("g.", Hertz::new(659.25, "E".to_string()));
-    swars.insert("G.", Hertz::new(698.46, "F".to_string()));
-    swars.insert("M.", Hertz::new(739.99, "F#".to_string()));
-    swars.insert("M'.", Hertz::new(783.99, "G".to_string()));
+/// Builds the swars in all five octaves (ati-mandra, mandra, madhya, taar
+/// and ati-taar) with Sa at `sa`, each named after its nearest western tone.
+pub fn initialise_swars(sa: &Hertz, tuning: &Tuning) -> HashMap<Pitch, Hertz> {
+    let mut swars: HashMap<Pitch, Hertz> = HashMap::new();
+    for octave in LOWEST_OCTAVE..=HIGHEST_OCTAVE {
+        for (_, semitones) in SAPTAK.iter() {
+            let pitch = Pitch::from_semitones(octave * 12 + semitones);
+            let hz = pitch.hertz(sa, tuning).unwrap();
+            swars.insert(pitch, hz);
+        }
+    }
 
     swars
 }
 
+/// Rounds a frequency to two decimal places
+pub fn round_freq(freq: f64) -> f64 {
+    (freq * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::raagas::constants::initialise_swars;
+    use crate::raagas::sound::Hertz;
+    use crate::raagas::pitch::Pitch;
+    use crate::raagas::tuning::Tuning;
+
+    fn pitch(s: &str) -> Pitch {
+        Pitch::new(s.to_string())
+    }
+
+    /// test swars are generated from a different Sa
+    #[test]
+    fn test_swars_from_tonic() {
+        let sa = "D".parse::<Hertz>().unwrap();
+        let swars = initialise_swars(&sa, &Tuning::default());
+        assert_eq!(swars.get(&pitch("S")).unwrap(), &Hertz::new(293.66, "D".to_string()));
+        assert_eq!(swars.get(&pitch("P")).unwrap(), &Hertz::new(440.0, "A".to_string()));
+        assert_eq!(swars.get(&pitch(".N")).unwrap(), &Hertz::new(277.18, "C#".to_string()));
+        assert_eq!(swars.get(&pitch("S.")).unwrap(), &Hertz::new(587.33, "D".to_string()));
+    }
+
+    /// test Sa can be given as a tone, with or without octave, or as a frequency
+    #[test]
+    fn test_tonic_from_str() {
+        let b3 = "B3".parse::<Hertz>().unwrap();
+        assert_eq!(b3.tone(), "B");
+        assert_eq!(initialise_swars(&b3, &Tuning::default()).get(&pitch("S")).unwrap().freq(), 246.94);
+        assert_eq!("Db".parse::<Hertz>().unwrap().tone(), "C#");
+        assert_eq!("261.63".parse::<Hertz>().unwrap().tone(), "C");
+        assert!("H".parse::<Hertz>().is_err());
+        assert!("nan".parse::<Hertz>().is_err());
+        assert!("inf".parse::<Hertz>().is_err());
+        assert!("C#x".parse::<Hertz>().is_err());
+    }
+
+    /// test the default swars are unchanged: Sa at C#
+    #[test]
+    fn test_swars_from_default_tonic() {
+        let sa = "C#".parse::<Hertz>().unwrap();
+        let swars = initialise_swars(&sa, &Tuning::default());
+        assert_eq!(swars.get(&pitch(".G")).unwrap(), &Hertz::new(174.61, "F".to_string()));
+        assert_eq!(swars.get(&pitch("M")).unwrap(), &Hertz::new(369.99, "F#".to_string()));
+        assert_eq!(swars.get(&pitch("M'.")).unwrap(), &Hertz::new(783.99, "G".to_string()));
+    }
+
+    /// test swars are generated in the ati-mandra and ati-taar octaves too
+    #[test]
+    fn test_swars_in_five_octaves() {
+        let sa = "C#".parse::<Hertz>().unwrap();
+        let swars = initialise_swars(&sa, &Tuning::default());
+        assert_eq!(swars.len(), 60);
+        assert_eq!(swars.get(&pitch("..S")).unwrap(), &Hertz::new(69.3, "C#".to_string()));
+        assert_eq!(swars.get(&pitch("S..")).unwrap(), &Hertz::new(1108.73, "C#".to_string()));
+        assert!("...S".parse::<Pitch>().is_err());
+    }
+}
@@ -0,0 +1,171 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use rodio::Source;
+
+/// Amplitude envelope of a swar (ADSR): rises to full volume over `attack`,
+/// falls to the `sustain` level over `decay`, and once the swar's duration is
+/// over fades out over `release`. Times are in seconds, `sustain` is a level
+/// between 0.0 and 1.0.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Envelope {
+    pub attack: f32,
+    pub decay: f32,
+    pub sustain: f32,
+    pub release: f32,
+}
+
+impl Envelope {
+    pub fn new(attack: f32, decay: f32, sustain: f32, release: f32) -> Self {
+        Envelope { attack, decay, sustain, release }
+    }
+
+    /// Returns the amplitude `t` seconds into a swar lasting `len` seconds;
+    /// after `len` the swar is in its release.
+    pub fn amplitude(&self, t: f32, len: f32) -> f32 {
+        if t >= len {
+            let released = if self.release > 0.0 {
+                (t - len) / self.release
+            } else {
+                1.0
+            };
+            return self.level(len) * (1.0 - released).max(0.0);
+        }
+
+        self.level(t)
+    }
+
+    // amplitude while the swar is held
+    fn level(&self, t: f32) -> f32 {
+        if t < self.attack {
+            return t / self.attack;
+        }
+        if t < self.attack + self.decay {
+            return 1.0 - (1.0 - self.sustain) * (t - self.attack) / self.decay;
+        }
+
+        self.sustain
+    }
+
+    /// Shapes the endless `src` into a swar lasting `len`, followed by its release
+    pub fn apply<S: Source<Item = f32>>(&self, src: S, len: Duration) -> Enveloped<S> {
+        Enveloped {
+            src,
+            envelope: *self,
+            len: len.as_secs_f32(),
+            num_sample: 0,
+        }
+    }
+}
+
+impl FromStr for Envelope {
+    type Err = String;
+
+    /// Parses `attack,decay,sustain,release`, e.g. `0.04,0.1,0.85,0.12`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let vals: Vec<f32> = s.split(',')
+            .map(|v| v.trim().parse::<f32>())
+            .collect::<Result<Vec<f32>, _>>()
+            .map_err(|_| format!("Invalid envelope {}", s))?;
+        if vals.len() != 4 || vals.iter().any(|v| *v < 0.0) || vals[2] > 1.0 {
+            return Err(format!(
+                "Invalid envelope {}, expected attack,decay,sustain,release", s
+            ));
+        }
+
+        Ok(Envelope::new(vals[0], vals[1], vals[2], vals[3]))
+    }
+}
+
+impl fmt::Display for Envelope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{},{},{}", self.attack, self.decay, self.sustain, self.release)
+    }
+}
+
+/// A source shaped by an envelope; it ends once the release is over.
+#[derive(Debug, Clone)]
+pub struct Enveloped<S> {
+    src: S,
+    envelope: Envelope,
+    len: f32,
+    num_sample: usize,
+}
+
+impl<S: Source<Item = f32>> Iterator for Enveloped<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let t = self.num_sample as f32
+            / (self.src.sample_rate() as f32 * self.src.channels() as f32);
+        if t >= self.len + self.envelope.release {
+            return None;
+        }
+        self.num_sample += 1;
+        let v = self.src.next()?;
+
+        Some(v * self.envelope.amplitude(t, self.len))
+    }
+}
+
+impl<S: Source<Item = f32>> Source for Enveloped<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.src.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.src.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs_f32(self.len + self.envelope.release))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use crate::raagas::envelope::Envelope;
+    use crate::raagas::instrument::Instrument;
+
+    /// test the amplitude through attack, decay, sustain and release
+    #[test]
+    fn test_envelope_amplitude() {
+        let env = Envelope::new(0.1, 0.1, 0.5, 0.2);
+        assert_eq!(env.amplitude(0.0, 1.0), 0.0);
+        assert_eq!(env.amplitude(0.05, 1.0), 0.5);
+        assert_eq!(env.amplitude(0.1, 1.0), 1.0);
+        assert!((env.amplitude(0.15, 1.0) - 0.75).abs() < 0.0001);
+        assert_eq!(env.amplitude(0.5, 1.0), 0.5);
+        assert!((env.amplitude(1.1, 1.0) - 0.25).abs() < 0.0001);
+        assert_eq!(env.amplitude(1.2, 1.0), 0.0);
+        // released before reaching full volume
+        assert!((env.amplitude(0.05, 0.05) - 0.5).abs() < 0.0001);
+    }
+
+    /// test an enveloped tone lasts for the swar and its release, and fades out
+    #[test]
+    fn test_enveloped_tone() {
+        let env = Envelope::new(0.01, 0.05, 0.8, 0.1);
+        let tone = Instrument::Sine.tone(440.0, 8000);
+        let samples: Vec<f32> = env.apply(tone, Duration::from_millis(500)).collect();
+        assert_eq!(samples.len(), 4800);
+        assert_eq!(samples[0], 0.0);
+        assert!(samples[4790..].iter().all(|s| s.abs() < 0.01));
+    }
+
+    /// test parsing an envelope
+    #[test]
+    fn test_envelope_from_str() {
+        let env = "0.04,0.1,0.85,0.12".parse::<Envelope>().unwrap();
+        assert_eq!(env, Envelope::new(0.04, 0.1, 0.85, 0.12));
+        assert_eq!(env.to_string(), "0.04,0.1,0.85,0.12");
+        assert!("0.04,0.1,1.5,0.12".parse::<Envelope>().is_err());
+        assert!("0.04,0.1".parse::<Envelope>().is_err());
+    }
+}
@@ -0,0 +1,233 @@
+use std::f64::consts::PI;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use rodio::Source;
+
+use crate::raagas::envelope::Envelope;
+
+pub const INSTRUMENTS: [&str; 4] = ["sine", "harmonium", "reed", "flute"];
+
+/// Timbre a swar is played with, built by additive synthesis from a
+/// harmonic profile.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Instrument {
+    Sine,
+    #[default]
+    Harmonium,
+    Reed,
+    Flute,
+}
+
+impl Instrument {
+    /// Relative amplitudes of the harmonics, starting with the fundamental
+    pub fn harmonics(&self) -> &'static [f32] {
+        match self {
+            Instrument::Sine => &[1.0],
+            // bright and rich in both odd and even harmonics
+            Instrument::Harmonium => &[1.0, 0.8, 0.6, 0.5, 0.35, 0.25, 0.2, 0.12],
+            // hollow, with the odd harmonics dominating
+            Instrument::Reed => &[1.0, 0.2, 0.7, 0.15, 0.5, 0.1, 0.3],
+            // breathy, close to a sine
+            Instrument::Flute => &[1.0, 0.35, 0.12, 0.05],
+        }
+    }
+
+    /// How a swar played on the instrument swells and fades
+    pub fn envelope(&self) -> Envelope {
+        match self {
+            Instrument::Sine => Envelope::new(0.01, 0.05, 0.9, 0.05),
+            // the bellows take a moment to fill and to empty
+            Instrument::Harmonium => Envelope::new(0.04, 0.1, 0.85, 0.12),
+            Instrument::Reed => Envelope::new(0.03, 0.08, 0.8, 0.1),
+            Instrument::Flute => Envelope::new(0.08, 0.1, 0.8, 0.15),
+        }
+    }
+
+    /// Returns an endless source of `freq` with this instrument's timbre
+    pub fn tone(&self, freq: f64, sample_rate: u32) -> Tone {
+        Tone::new(freq, self.harmonics(), sample_rate)
+    }
+}
+
+impl FromStr for Instrument {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "sine" => Ok(Instrument::Sine),
+            "harmonium" => Ok(Instrument::Harmonium),
+            "reed" => Ok(Instrument::Reed),
+            "flute" => Ok(Instrument::Flute),
+            _ => Err(format!(
+                "Instrument {} is unsupported, instruments allowed: {}",
+                s, INSTRUMENTS.join(",")
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Instrument {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Instrument::Sine => "sine",
+            Instrument::Harmonium => "harmonium",
+            Instrument::Reed => "reed",
+            Instrument::Flute => "flute",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// An endless mono source summing the harmonics of a frequency; the
+/// amplitudes are scaled so the tone never exceeds 1.0. The frequency can
+/// glide to another one, e.g. for a meend, or oscillate around itself, e.g.
+/// for an andolan.
+#[derive(Debug, Clone)]
+pub struct Tone {
+    freq: f64,
+    glide: Option<(f64, f64)>,
+    // depth in cents and rate in Hz
+    oscillation: Option<(f64, f64)>,
+    amplitudes: Vec<f32>,
+    sample_rate: u32,
+    num_sample: usize,
+    // position within the fundamental's cycle
+    phase: f64,
+}
+
+impl Tone {
+    pub fn new(freq: f64, harmonics: &[f32], sample_rate: u32) -> Self {
+        let total: f32 = harmonics.iter().sum();
+        Tone {
+            freq,
+            glide: None,
+            oscillation: None,
+            amplitudes: harmonics.iter().map(|a| a / total).collect(),
+            sample_rate,
+            num_sample: 0,
+            phase: 0.0,
+        }
+    }
+
+    /// Returns the tone sweeping continuously to `to` over `len`, evenly in
+    /// pitch, and holding `to` after that
+    pub fn glide(mut self, to: f64, len: Duration) -> Self {
+        self.glide = Some((to, len.as_secs_f64()));
+        self
+    }
+
+    /// Returns the tone swinging `depth` cents either side of its frequency,
+    /// `rate` times a second
+    pub fn oscillate(mut self, depth: f64, rate: f64) -> Self {
+        self.oscillation = Some((depth, rate));
+        self
+    }
+
+    /// Returns the frequency `t` seconds into the tone
+    pub fn freq_at(&self, t: f64) -> f64 {
+        let freq = match self.glide {
+            Some((to, len)) if len > 0.0 => {
+                let progress = (t / len).min(1.0);
+                self.freq * (to / self.freq).powf(progress)
+            },
+            Some((to, _)) => to,
+            _ => self.freq,
+        };
+        match self.oscillation {
+            Some((depth, rate)) => {
+                let cents = depth * (2.0 * PI * rate * t).sin();
+                freq * 2f64.powf(cents / 1200.0)
+            },
+            _ => freq,
+        }
+    }
+}
+
+impl Iterator for Tone {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let t = self.num_sample as f64 / self.sample_rate as f64;
+        self.num_sample = self.num_sample.wrapping_add(1);
+        let phase = self.phase;
+        let value: f64 = self.amplitudes.iter()
+            .enumerate()
+            .map(|(k, a)| *a as f64 * (2.0 * PI * (k + 1) as f64 * phase).sin())
+            .sum();
+        self.phase = (self.phase + self.freq_at(t) / self.sample_rate as f64).fract();
+
+        Some(value as f32)
+    }
+}
+
+impl Source for Tone {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use crate::raagas::instrument::Instrument;
+
+    /// test a sine instrument produces a plain sine wave
+    #[test]
+    fn test_sine_tone() {
+        let tone = Instrument::Sine.tone(1000.0, 8000);
+        let samples: Vec<f32> = tone.take(3).collect();
+        assert_eq!(samples[0], 0.0);
+        assert!((samples[2] - 1.0).abs() < 0.0001);
+    }
+
+    /// test a gliding tone sweeps from one frequency to the other
+    #[test]
+    fn test_glide_tone() {
+        let tone = Instrument::Sine.tone(200.0, 8000).glide(400.0, Duration::from_secs(1));
+        assert_eq!(tone.freq_at(0.0), 200.0);
+        assert!((tone.freq_at(0.5) - 282.84).abs() < 0.01);
+        assert_eq!(tone.freq_at(2.0), 400.0);
+
+        // count the cycles in the first and the last tenth of the glide
+        let samples: Vec<f32> = tone.take(8000).collect();
+        let cycles = |s: &[f32]| s.windows(2).filter(|w| w[0] < 0.0 && w[1] >= 0.0).count();
+        assert_eq!(cycles(&samples[..800]), 20);
+        assert_eq!(cycles(&samples[7200..]), 39);
+    }
+
+    /// test an oscillating tone swings either side of its frequency
+    #[test]
+    fn test_oscillating_tone() {
+        let tone = Instrument::Sine.tone(400.0, 8000).oscillate(100.0, 1.0);
+        assert_eq!(tone.freq_at(0.0), 400.0);
+        assert!((tone.freq_at(0.25) - 423.78).abs() < 0.01);
+        assert!((tone.freq_at(0.75) - 377.55).abs() < 0.01);
+        assert!((tone.freq_at(1.0) - 400.0).abs() < 0.0001);
+    }
+
+    /// test the harmonics of every instrument are scaled to stay within [-1, 1]
+    #[test]
+    fn test_tone_amplitude() {
+        for i in ["sine", "harmonium", "reed", "flute"].iter() {
+            let instrument = i.parse::<Instrument>().unwrap();
+            assert_eq!(instrument.to_string(), *i);
+            let tone = instrument.tone(277.18, 8000);
+            assert!(tone.take(8000).all(|s| s.abs() <= 1.0));
+        }
+        assert!("sitar".parse::<Instrument>().is_err());
+    }
+}
@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::raagas::constants::BPM;
+
+pub const LAYAS: [&str; 3] = ["vilambit", "madhya", "drut"];
+// sections of a composition that can be given a tempo of their own, in the
+// order they are played
+pub const SECTIONS: [&str; 7] = ["sthayi", "antara", "taans", "tihayi", "alap", "jor", "jhala"];
+
+/// The named speeds a composition is played at: slow, medium and fast, each
+/// twice as fast as the one before
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Laya {
+    Vilambit,
+    Madhya,
+    Drut,
+}
+
+impl Laya {
+    /// Returns the tempo of the laya, in beats per minute
+    pub fn bpm(&self) -> f32 {
+        match self {
+            Laya::Vilambit => 40.0,
+            Laya::Madhya => 80.0,
+            Laya::Drut => 160.0,
+        }
+    }
+}
+
+impl FromStr for Laya {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "vilambit" => Ok(Laya::Vilambit),
+            "madhya" => Ok(Laya::Madhya),
+            "drut" => Ok(Laya::Drut),
+            _ => Err(format!("Laya {} is unsupported, layas allowed: {}", s, LAYAS.join(","))),
+        }
+    }
+}
+
+impl fmt::Display for Laya {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Laya::Vilambit => "vilambit",
+            Laya::Madhya => "madhya",
+            Laya::Drut => "drut",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// A tempo in beats per minute, either steady (e.g. `120` or `drut`) or
+/// changing gradually over the section it is set for (e.g. `80-120` or
+/// `madhya-drut`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tempo {
+    pub bpm: f32,
+    pub to: Option<f32>,
+}
+
+impl Tempo {
+    pub fn new(bpm: f32) -> Self {
+        Tempo { bpm, to: None }
+    }
+
+    /// Returns the tempo changing gradually from `bpm` to `to`
+    pub fn ramp(bpm: f32, to: f32) -> Self {
+        Tempo { bpm, to: Some(to) }
+    }
+
+    /// Returns the length of a beat at the start of the tempo, in seconds
+    pub fn beat_len(&self) -> f32 {
+        60.0 / self.bpm
+    }
+}
+
+impl Default for Tempo {
+    fn default() -> Self {
+        Tempo::new(BPM)
+    }
+}
+
+impl From<Laya> for Tempo {
+    fn from(laya: Laya) -> Self {
+        Tempo::new(laya.bpm())
+    }
+}
+
+/// Parses a tempo given as a laya or in beats per minute, which must be a
+/// positive, finite number
+pub fn parse_bpm(s: &str) -> Result<f32, String> {
+    if let Ok(laya) = s.parse::<Laya>() {
+        return Ok(laya.bpm());
+    }
+    match s.trim().parse::<f32>() {
+        Ok(bpm) if bpm > 0.0 && bpm.is_finite() => Ok(bpm),
+        _ => Err(format!(
+            "Tempo {} should be in beats per minute or one of: {}", s, LAYAS.join(",")
+        )),
+    }
+}
+
+impl FromStr for Tempo {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('-') {
+            Some((from, to)) => Ok(Tempo::ramp(parse_bpm(from)?, parse_bpm(to)?)),
+            _ => Ok(Tempo::new(parse_bpm(s)?)),
+        }
+    }
+}
+
+impl fmt::Display for Tempo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.to {
+            Some(to) => write!(f, "{}-{}", self.bpm, to),
+            _ => write!(f, "{}", self.bpm),
+        }
+    }
+}
+
+/// The tempo each section of a composition is played at, e.g. speeding up
+/// across the sthayi and antara and jumping to drut for the tihayi. A section
+/// without a tempo carries on at the tempo the one before it ended at.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TempoMap {
+    tempos: HashMap<String, Tempo>,
+}
+
+impl TempoMap {
+    pub fn new() -> Self {
+        TempoMap::default()
+    }
+
+    /// Sets the tempo of `section`, one of `SECTIONS`
+    pub fn insert(&mut self, section: &str, tempo: Tempo) -> Result<(), String> {
+        if !SECTIONS.contains(&section) {
+            return Err(format!(
+                "Section {} can't be given a tempo, sections allowed: {}", section, SECTIONS.join(",")
+            ));
+        }
+        self.tempos.insert(section.to_string(), tempo);
+
+        Ok(())
+    }
+
+    pub fn get(&self, section: &str) -> Option<&Tempo> {
+        self.tempos.get(section)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tempos.is_empty()
+    }
+}
+
+/// Returns how long (in seconds) `beats` beats last, starting at `bpm` and
+/// changing by `accel` beats per minute every beat
+pub fn secs(bpm: f32, accel: f32, beats: f32) -> f32 {
+    if accel.abs() < 1e-6 {
+        60.0 * beats / bpm
+    } else {
+        // the time a beat takes, 60 / bpm, summed over the changing tempo
+        60.0 / accel * ((bpm + accel * beats) / bpm).ln()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::raagas::laya::{secs, Laya, Tempo, TempoMap};
+
+    /// test tempos can be given in beats per minute, as a laya or as a change
+    /// from one to another
+    #[test]
+    fn test_tempo_from_str() {
+        assert_eq!("120".parse::<Tempo>(), Ok(Tempo::new(120.0)));
+        assert_eq!("Drut".parse::<Tempo>(), Ok(Tempo::from(Laya::Drut)));
+        assert_eq!("80-120".parse::<Tempo>(), Ok(Tempo::ramp(80.0, 120.0)));
+        assert_eq!("madhya-drut".parse::<Tempo>(), Ok(Tempo::ramp(80.0, 160.0)));
+        assert_eq!(Tempo::ramp(80.0, 120.0).to_string(), "80-120");
+        assert!("0".parse::<Tempo>().is_err());
+        assert!("nan".parse::<Tempo>().is_err());
+        assert!("inf".parse::<Tempo>().is_err());
+        assert!("ati-drut".parse::<Tempo>().is_err());
+        assert_eq!(Tempo::new(120.0).beat_len(), 0.5);
+    }
+
+    /// test a steady tempo and one speeding up over the beats
+    #[test]
+    fn test_secs() {
+        assert_eq!(secs(120.0, 0.0, 4.0), 2.0);
+        // speeding up from 60 to 120 over 4 beats takes less than 4s, more than 2s
+        let t = secs(60.0, 15.0, 4.0);
+        assert!(t < 4.0 && t > 2.0);
+        assert!((t - 4.0 * 2f32.ln()).abs() < 1e-4);
+    }
+
+    /// test only the sections of a swarmaalika can be given a tempo
+    #[test]
+    fn test_tempo_map() {
+        let mut tempos = TempoMap::new();
+        assert!(tempos.insert("tihayi", Tempo::from(Laya::Drut)).is_ok());
+        assert!(tempos.insert("pakad", Tempo::new(60.0)).is_err());
+        assert_eq!(tempos.get("tihayi"), Some(&Tempo::new(160.0)));
+        assert_eq!(tempos.get("sthayi"), None);
+    }
+}
@@ -2,14 +2,30 @@ use crate::raagas::raag::raag::Raag;
 use crate::raagas::swars::Swar;
 use crate::raagas::swarblock::{SwarBlock, SwarInSwarBlock};
 use crate::raagas::swarblocks::SwarBlocks;
+use crate::raagas::render::Renderer;
 
 pub mod utils;
 pub mod constants;
 pub mod swarmaalika;
+pub mod composition;
 pub mod swars;
 pub mod sound;
+pub mod pitch;
+pub mod beats;
+pub mod arrangement;
 pub mod taal;
+pub mod tabla;
 pub mod raag;
+pub mod render;
+pub mod tuning;
+pub mod instrument;
+pub mod envelope;
+pub mod tanpura;
+pub mod timeline;
+pub mod laya;
+pub mod notation;
+pub mod bols;
+pub mod bhatkhande;
 pub(crate) mod swarblock;
 pub(crate) mod swarbeat;
 mod swarblocks;
@@ -22,6 +38,11 @@ pub enum Melody {
     Raag(Raag),
 }
 
+/// Offline counterpart of `play`: appends what would be played to a `Renderer`
+pub trait Render {
+    fn render(&self, r: &mut Renderer);
+}
+
 pub(crate) trait PureRandomiser {
     fn randomise(&self, n_swars: usize) -> Result<Vec<Swar>, String>;
 }
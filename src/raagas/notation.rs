@@ -0,0 +1,320 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+/// Phrases of notation defined once in a composition by name, and referred
+/// to as `$name` in its lines
+pub type Phrases = HashMap<String, String>;
+
+/// What is wrong with a token of swar notation
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseErrorKind {
+    /// not a swar of any saptak, e.g. `X` or `S...`
+    UnknownSwar,
+    /// a kan swar not followed by exactly one swar, e.g. `P/` or `P/M/G`
+    Kan,
+    /// a meend not gliding to a swar, e.g. `G~`
+    Meend,
+    /// an andolan whose depth or rate isn't a number, e.g. `g^x`
+    Andolan,
+    /// a `-` with no swar before it to extend
+    NothingToExtend,
+    /// a group not opened or closed, or repeated other than `x<N>`, e.g.
+    /// `(S R` or `(S R)3`
+    Group,
+    /// a `$name` that no phrase is defined as
+    UnknownPhrase,
+    /// a phrase that refers back to itself
+    RecursivePhrase,
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseErrorKind::UnknownSwar => write!(f, "unknown swar"),
+            ParseErrorKind::Kan => write!(f, "a kan swar should be followed by one swar"),
+            ParseErrorKind::Meend => write!(f, "a meend should glide to a swar"),
+            ParseErrorKind::Andolan => write!(f, "an andolan's depth and rate should be numbers"),
+            ParseErrorKind::NothingToExtend => write!(f, "no swar before it to extend"),
+            ParseErrorKind::Group => write!(f, "a group should be `(...)` or repeated as `(...)x<N>`"),
+            ParseErrorKind::UnknownPhrase => write!(f, "unknown phrase"),
+            ParseErrorKind::RecursivePhrase => write!(f, "a phrase can't refer to itself"),
+        }
+    }
+}
+
+/// An error in swar notation: the offending token, its column (from 1) and
+/// the line of the composition it is on, e.g. `sthayi lineA`, if known
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    pub token: String,
+    pub column: usize,
+    pub line: Option<String>,
+}
+
+impl ParseError {
+    pub fn new(kind: ParseErrorKind, token: &str, column: usize) -> Self {
+        ParseError {
+            kind,
+            token: token.to_string(),
+            column,
+            line: None,
+        }
+    }
+
+    /// Returns the error placed on `line` of the composition
+    pub fn with_line(mut self, line: &str) -> Self {
+        self.line = Some(line.to_string());
+        self
+    }
+
+    /// Returns the error with its column moved on by `by`, for notation that
+    /// was parsed from the middle of a line
+    pub fn shifted(mut self, by: usize) -> Self {
+        self.column += by;
+        self
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(line) = &self.line {
+            write!(f, "{}, ", line)?;
+        }
+        write!(f, "column {}: {} at {:?}", self.column, self.kind, self.token)
+    }
+}
+
+impl Error for ParseError {}
+
+/// Splits notation at `sep`, returning each part with its offset (in
+/// characters) from the start of `s`
+pub fn split_with_offsets(s: &str, sep: char) -> Vec<(usize, &str)> {
+    let mut offset = 0;
+    s.split(sep)
+        .map(|part| {
+            let at = offset;
+            offset += part.chars().count() + 1;
+            (at, part)
+        })
+        .collect()
+}
+
+/// Splits notation into its whitespace separated tokens, each with its
+/// column (from 1)
+pub fn tokens(s: &str) -> Vec<(usize, &str)> {
+    let mut tokens = Vec::new();
+    let mut start: Option<(usize, usize)> = None;
+    for (col, (i, c)) in s.char_indices().enumerate() {
+        match (c.is_whitespace(), start) {
+            (true, Some((at, col))) => {
+                tokens.push((col + 1, &s[at..i]));
+                start = None;
+            },
+            (false, None) => start = Some((i, col)),
+            _ => {},
+        }
+    }
+    if let Some((at, col)) = start {
+        tokens.push((col + 1, &s[at..]));
+    }
+
+    tokens
+}
+
+// a token of notation with groups: its opening, its closing (and the number
+// of times it is played) or a swarbeat or phrase
+enum Item<'a> {
+    Open,
+    Close(usize),
+    Word(&'a str),
+}
+
+// splits the tokens of notation into groups and words, e.g. `(S` into an
+// opening and `S`, and `R)x3` into `R` and a closing, with their columns
+fn items(s: &str) -> Result<Vec<(usize, Item<'_>)>, ParseError> {
+    let mut items = Vec::new();
+    for (col, token) in tokens(s) {
+        let mut rest = token;
+        let mut col = col;
+        while let Some(r) = rest.strip_prefix('(') {
+            items.push((col, Item::Open));
+            rest = r;
+            col += 1;
+        }
+        let word = &rest[..rest.find(')').unwrap_or(rest.len())];
+        if !word.is_empty() {
+            items.push((col, Item::Word(word)));
+            rest = &rest[word.len()..];
+            col += word.chars().count();
+        }
+        while let Some(r) = rest.strip_prefix(')') {
+            let (times, r) = match r.strip_prefix('x') {
+                Some(r) => {
+                    let digits = r.find(|c: char| !c.is_ascii_digit()).unwrap_or(r.len());
+                    let times = r[..digits].parse::<usize>()
+                        .map_err(|_| ParseError::new(ParseErrorKind::Group, token, col))?;
+                    (times, &r[digits..])
+                },
+                _ => (1, r),
+            };
+            items.push((col, Item::Close(times)));
+            col += rest.len() - r.len();
+            rest = r;
+        }
+        if !rest.is_empty() {
+            return Err(ParseError::new(ParseErrorKind::Group, token, col));
+        }
+    }
+
+    Ok(items)
+}
+
+/// Returns the swarbeats of notation with its groups repeated, e.g.
+/// `(S R)x2 G` as `S R S R G`, and `$name` replaced by the phrase `name`;
+/// each swarbeat with the column it came from (that of the phrase for the
+/// swarbeats of a phrase)
+pub fn expand(s: &str, phrases: &Phrases) -> Result<Vec<(usize, String)>, ParseError> {
+    expand_within(s, phrases, &mut Vec::new())
+}
+
+// expands notation within the phrases `within`, so a phrase can't refer to
+// itself
+fn expand_within(s: &str,
+                 phrases: &Phrases,
+                 within: &mut Vec<String>) -> Result<Vec<(usize, String)>, ParseError> {
+    // the swarbeats of each group opened and not yet closed, with its column
+    let mut groups: Vec<(usize, Vec<(usize, String)>)> = vec![(1, Vec::new())];
+    for (col, item) in items(s)? {
+        match item {
+            Item::Open => groups.push((col, Vec::new())),
+            Item::Close(times) => {
+                if groups.len() == 1 {
+                    return Err(ParseError::new(ParseErrorKind::Group, ")", col));
+                }
+                let (_, group) = groups.pop().unwrap();
+                let outer = &mut groups.last_mut().unwrap().1;
+                for _ in 0..times {
+                    outer.extend(group.iter().cloned());
+                }
+            },
+            Item::Word(word) => {
+                let beats = match word.strip_prefix('$') {
+                    Some(name) => {
+                        let phrase = phrases.get(name)
+                            .ok_or(ParseError::new(ParseErrorKind::UnknownPhrase, word, col))?;
+                        if within.iter().any(|p| p == name) {
+                            return Err(ParseError::new(ParseErrorKind::RecursivePhrase, word, col));
+                        }
+                        within.push(name.to_string());
+                        let beats = expand_within(phrase, phrases, within)
+                            .map_err(|e| ParseError::new(e.kind, &e.token, col))?;
+                        within.pop();
+                        beats.into_iter().map(|(_, b)| (col, b)).collect()
+                    },
+                    _ => vec![(col, word.to_string())],
+                };
+                groups.last_mut().unwrap().1.extend(beats);
+            },
+        }
+    }
+    if groups.len() > 1 {
+        let (col, _) = groups.last().unwrap();
+        return Err(ParseError::new(ParseErrorKind::Group, "(", *col));
+    }
+
+    Ok(groups.pop().unwrap().1)
+}
+
+/// Returns the swarbeats with repetitions written as groups, e.g. `S R S R
+/// G` as `(S R)x2 G`; of the repetitions starting at a swarbeat, the one
+/// covering the most swarbeats is taken
+pub fn compress(beats: &[String]) -> String {
+    let mut out: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < beats.len() {
+        // the length of the repeated phrase, and the times it is repeated
+        let mut best = (1, 1);
+        for len in 1..=(beats.len() - i) / 2 {
+            let phrase = &beats[i..i + len];
+            // a run of held swars is left as it is
+            if phrase.iter().all(|b| b == "-") {
+                continue;
+            }
+            let times = 1 + beats[i + len..]
+                .chunks(len)
+                .take_while(|c| *c == phrase)
+                .count();
+            if times > 1 && len * times > best.0 * best.1 {
+                best = (len, times);
+            }
+        }
+
+        match best {
+            (len, times) if times > 1 => {
+                out.push(format!("({})x{}", beats[i..i + len].join(" "), times));
+                i += len * times;
+            },
+            _ => {
+                out.push(beats[i].clone());
+                i += 1;
+            },
+        }
+    }
+
+    out.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::raagas::notation::{tokens, split_with_offsets, expand, compress, Phrases, ParseError, ParseErrorKind};
+
+    /// test tokens are found with their columns, however they are spaced
+    #[test]
+    fn test_tokens() {
+        assert_eq!(tokens(" S  R:G\t- "), vec![(2, "S"), (5, "R:G"), (9, "-")]);
+        assert_eq!(split_with_offsets("S::G", ':'), vec![(0, "S"), (2, ""), (3, "G")]);
+    }
+
+    /// test the error shows where the typo is
+    #[test]
+    fn test_parse_error_display() {
+        let e = ParseError::new(ParseErrorKind::UnknownSwar, "X", 3).shifted(4).with_line("sthayi lineA");
+        assert_eq!(e.to_string(), "sthayi lineA, column 7: unknown swar at \"X\"");
+    }
+
+    /// test groups are repeated, within groups too, and phrases replaced
+    #[test]
+    fn test_expand() {
+        let mut phrases = Phrases::new();
+        phrases.insert("mukhda".to_string(), "G:M P".to_string());
+        phrases.insert("tihai".to_string(), "($mukhda -)x3".to_string());
+        let beats = |s: &str| -> Vec<String> {
+            expand(s, &phrases).unwrap().into_iter().map(|(_, b)| b).collect()
+        };
+        assert_eq!(beats("(S R)x2 G"), vec!["S", "R", "S", "R", "G"]);
+        assert_eq!(beats("((S)x2 R)x2"), vec!["S", "S", "R", "S", "S", "R"]);
+        assert_eq!(beats("S $mukhda"), vec!["S", "G:M", "P"]);
+        assert_eq!(beats("$tihai").len(), 9);
+        assert_eq!(expand("S $mukhda", &phrases).unwrap()[2], (3, "P".to_string()));
+
+        let err = |s: &str| expand(s, &phrases).unwrap_err();
+        assert_eq!((err("S (R G").kind, err("S (R G").column), (ParseErrorKind::Group, 3));
+        assert_eq!(err("S R)x2").kind, ParseErrorKind::Group);
+        assert_eq!(err("(S R)3").kind, ParseErrorKind::Group);
+        assert_eq!((err("S $antra").kind, err("S $antra").column), (ParseErrorKind::UnknownPhrase, 3));
+        let mut phrases = Phrases::new();
+        phrases.insert("loop".to_string(), "S $loop".to_string());
+        assert_eq!(expand("$loop", &phrases).unwrap_err().kind, ParseErrorKind::RecursivePhrase);
+    }
+
+    /// test repetitions are written back as groups
+    #[test]
+    fn test_compress() {
+        let beats = |s: &str| -> Vec<String> { s.split(' ').map(|b| b.to_string()).collect() };
+        assert_eq!(compress(&beats("S R G R S R G R S R G R P")), "(S R G R)x3 P");
+        assert_eq!(compress(&beats("S - - M P P")), "S - - M (P)x2");
+        assert_eq!(compress(&beats("S R G")), "S R G");
+    }
+}
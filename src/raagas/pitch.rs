@@ -0,0 +1,358 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::raagas::constants::{round_freq, HIGHEST_OCTAVE, LOWEST_OCTAVE};
+use crate::raagas::sound::Hertz;
+use crate::raagas::tuning::Tuning;
+
+/// The seven swars of a saptak, in order from Sa
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Degree {
+    Sa,
+    Re,
+    Ga,
+    Ma,
+    Pa,
+    Dha,
+    Ni,
+}
+
+impl Degree {
+    /// Returns the letter the swar is written with, e.g. `S`
+    pub fn letter(&self) -> char {
+        match self {
+            Degree::Sa => 'S',
+            Degree::Re => 'R',
+            Degree::Ga => 'G',
+            Degree::Ma => 'M',
+            Degree::Pa => 'P',
+            Degree::Dha => 'D',
+            Degree::Ni => 'N',
+        }
+    }
+
+    fn from_letter(c: char) -> Option<Degree> {
+        match c.to_ascii_uppercase() {
+            'S' => Some(Degree::Sa),
+            'R' => Some(Degree::Re),
+            'G' => Some(Degree::Ga),
+            'M' => Some(Degree::Ma),
+            'P' => Some(Degree::Pa),
+            'D' => Some(Degree::Dha),
+            'N' => Some(Degree::Ni),
+            _ => None,
+        }
+    }
+
+    // semitones from Sa of the shuddh swar
+    fn semitones(&self) -> i32 {
+        match self {
+            Degree::Sa => 0,
+            Degree::Re => 2,
+            Degree::Ga => 4,
+            Degree::Ma => 5,
+            Degree::Pa => 7,
+            Degree::Dha => 9,
+            Degree::Ni => 11,
+        }
+    }
+}
+
+/// Whether a swar is shuddh (pure), komal (flat: `r`, `g`, `d`, `n`) or
+/// tivra (sharp: `M'`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Variant {
+    Shuddh,
+    Komal,
+    Tivra,
+}
+
+/// A swar of a saptak in an octave counted from the madhya saptak: `-1` is
+/// mandra (`.S`), `1` taar (`S.`), and `-2` and `2` ati-mandra (`..S`) and
+/// ati-taar (`S..`); optionally a number of cents off the swar, e.g. `g+15`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Pitch {
+    degree: Degree,
+    variant: Variant,
+    octave: i32,
+    cents: i32,
+}
+
+impl Pitch {
+    /// Returns the pitch written as `name` in notation, e.g. `..P`, `g` or
+    /// `M'.`, panicking if it isn't a swar (`parse` returns the error instead)
+    pub fn new(name: String) -> Self {
+        name.parse().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Returns the swar `degree` of the `variant` in `octave`, if the swar
+    /// has such a variant, e.g. komal Ga but not tivra Pa
+    pub fn of(degree: Degree, variant: Variant, octave: i32) -> Result<Self, String> {
+        let valid = match variant {
+            Variant::Shuddh => true,
+            Variant::Komal => matches!(degree, Degree::Re | Degree::Ga | Degree::Dha | Degree::Ni),
+            Variant::Tivra => degree == Degree::Ma,
+        };
+        if !valid {
+            return Err(format!("{} has no {:?} variant", degree.letter(), variant));
+        }
+
+        Ok(Pitch { degree, variant, octave, cents: 0 })
+    }
+
+    /// Returns the pitch `semitones` away from madhya Sa, komal swars
+    /// standing for the notes between shuddh swars and tivra Ma for the one
+    /// between Ma and Pa
+    pub fn from_semitones(semitones: i32) -> Self {
+        let octave = semitones.div_euclid(12);
+        let (degree, variant) = match semitones.rem_euclid(12) {
+            0 => (Degree::Sa, Variant::Shuddh),
+            1 => (Degree::Re, Variant::Komal),
+            2 => (Degree::Re, Variant::Shuddh),
+            3 => (Degree::Ga, Variant::Komal),
+            4 => (Degree::Ga, Variant::Shuddh),
+            5 => (Degree::Ma, Variant::Shuddh),
+            6 => (Degree::Ma, Variant::Tivra),
+            7 => (Degree::Pa, Variant::Shuddh),
+            8 => (Degree::Dha, Variant::Komal),
+            9 => (Degree::Dha, Variant::Shuddh),
+            10 => (Degree::Ni, Variant::Komal),
+            _ => (Degree::Ni, Variant::Shuddh),
+        };
+
+        Pitch { degree, variant, octave, cents: 0 }
+    }
+
+    /// Returns the pitch `cents` off the swar
+    pub fn with_cents(mut self, cents: i32) -> Self {
+        self.cents = cents;
+        self
+    }
+
+    pub fn degree(&self) -> Degree {
+        self.degree
+    }
+
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    pub fn octave(&self) -> i32 {
+        self.octave
+    }
+
+    pub fn cents(&self) -> i32 {
+        self.cents
+    }
+
+    /// Returns the number of semitones from madhya Sa, ignoring any cents
+    pub fn semitones(&self) -> i32 {
+        let offset = match self.variant {
+            Variant::Shuddh => 0,
+            Variant::Komal => -1,
+            Variant::Tivra => 1,
+        };
+        self.octave * 12 + self.degree.semitones() + offset
+    }
+
+    /// Returns the number of semitones up from the pitch to `other`
+    /// (negative if `other` is lower)
+    pub fn interval(&self, other: &Pitch) -> i32 {
+        other.semitones() - self.semitones()
+    }
+
+    /// Returns the pitch `semitones` higher (or lower, if negative), as far
+    /// off its swar as this one
+    pub fn transposed(&self, semitones: i32) -> Self {
+        Pitch::from_semitones(self.semitones() + semitones).with_cents(self.cents)
+    }
+
+    /// Returns the frequency of the pitch with Sa at `sa`, in `tuning`; `None`
+    /// outside the octaves swars are played in
+    pub fn hertz(&self, sa: &Hertz, tuning: &Tuning) -> Option<Hertz> {
+        if !(LOWEST_OCTAVE..=HIGHEST_OCTAVE).contains(&self.octave) {
+            return None;
+        }
+        let ratio = tuning.ratio(self.degree, self.variant)?;
+        let hz = round_freq(sa.freq() * ratio * 2f64.powi(self.octave));
+        match self.cents {
+            0 => Some(Hertz::from_freq(hz)),
+            cents => Some(Hertz::from_freq(round_freq(hz * 2f64.powf(cents as f64 / 1200.0)))),
+        }
+    }
+
+    /// Returns the pitch `by` octaves higher (or lower, if negative); `None`
+    /// if that is past ati-mandra or ati-taar, where swars aren't played
+    pub fn shifted(&self, by: i32) -> Option<Self> {
+        let octave = self.octave + by;
+        if !(LOWEST_OCTAVE..=HIGHEST_OCTAVE).contains(&octave) {
+            return None;
+        }
+
+        Some(Pitch { octave, ..self.clone() })
+    }
+
+    /// Returns the pitch an octave lower; `None` if it's in ati-mandra
+    pub fn lower(&self) -> Option<Self> {
+        self.shifted(-1)
+    }
+
+    /// Returns the pitch an octave higher; `None` if it's in ati-taar
+    pub fn higher(&self) -> Option<Self> {
+        self.shifted(1)
+    }
+}
+
+impl Default for Pitch {
+    fn default() -> Self {
+        Pitch::from_semitones(0)
+    }
+}
+
+impl Ord for Pitch {
+    /// Orders pitches from the lowest to the highest
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.semitones(), self.cents).cmp(&(other.semitones(), other.cents))
+    }
+}
+
+impl PartialOrd for Pitch {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl FromStr for Pitch {
+    type Err = String;
+
+    /// Returns a pitch from its notation: the swar's letter (lowercase for
+    /// komal, followed by `'` for tivra), dots before it for each octave
+    /// below madhya or after it for each octave above, and then any cents
+    /// off the swar, e.g. `.n`, `M'.` or `g+15`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("{} is not a swar", s);
+        let (name, cents) = match s.find(['+', '-']) {
+            Some(at) => (&s[..at], s[at..].parse::<i32>().map_err(|_| invalid())?),
+            _ => (s, 0),
+        };
+
+        let below = name.chars().take_while(|c| *c == '.').count();
+        let above = name.chars().rev().take_while(|c| *c == '.').count();
+        if below > 0 && above > 0 || below + above >= name.len() {
+            return Err(invalid());
+        }
+        let octave = above as i32 - below as i32;
+        if !(LOWEST_OCTAVE..=HIGHEST_OCTAVE).contains(&octave) {
+            return Err(format!("{} is beyond the octaves swars are played in", s));
+        }
+
+        let mut chars = name[below..name.len() - above].chars();
+        let letter = chars.next().ok_or_else(invalid)?;
+        let degree = Degree::from_letter(letter).ok_or_else(invalid)?;
+        let variant = match (letter.is_lowercase(), chars.as_str()) {
+            (false, "") => Variant::Shuddh,
+            (true, "") => Variant::Komal,
+            (false, "'") => Variant::Tivra,
+            _ => return Err(invalid()),
+        };
+
+        Ok(Pitch::of(degree, variant, octave).map_err(|_| invalid())?.with_cents(cents))
+    }
+}
+
+impl fmt::Display for Pitch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let dots = ".".repeat(self.octave.unsigned_abs() as usize);
+        let swar = match self.variant {
+            Variant::Shuddh => self.degree.letter().to_string(),
+            Variant::Komal => self.degree.letter().to_ascii_lowercase().to_string(),
+            Variant::Tivra => format!("{}'", self.degree.letter()),
+        };
+        match self.octave {
+            o if o < 0 => write!(f, "{}{}", dots, swar)?,
+            _ => write!(f, "{}{}", swar, dots)?,
+        }
+        if self.cents != 0 {
+            write!(f, "{:+}", self.cents)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::raagas::pitch::{Degree, Pitch, Variant};
+    use crate::raagas::constants;
+    use crate::raagas::sound::Hertz;
+    use crate::raagas::tuning::Tuning;
+
+    fn pitch(s: &str) -> Pitch {
+        s.parse().unwrap()
+    }
+
+    /// test pitches are read from notation and written back the same
+    #[test]
+    fn test_pitch_from_str() {
+        for s in ["S", "r", "M'", ".n", "..P", "S..", "M'.", "g+15", ".d-10"] {
+            assert_eq!(pitch(s).to_string(), s);
+        }
+        let p = pitch("M'.");
+        assert_eq!((p.degree(), p.variant(), p.octave()), (Degree::Ma, Variant::Tivra, 1));
+        assert_eq!(pitch("g+15").cents(), 15);
+        for s in ["", "X", "p", "R'", ".S.", "S...", "g+x", "."] {
+            assert!(s.parse::<Pitch>().is_err(), "{}", s);
+        }
+        assert!(Pitch::of(Degree::Pa, Variant::Komal, 0).is_err());
+    }
+
+    /// test pitches are ordered from low to high, and compared without
+    /// looking up their frequency
+    #[test]
+    fn test_pitch_ord() {
+        let mut pitches: Vec<Pitch> = ["S.", ".N", "g", "R", "M'", "M", "..P"].iter().map(|s| pitch(s)).collect();
+        pitches.sort();
+        let sorted: Vec<String> = pitches.iter().map(|p| p.to_string()).collect();
+        assert_eq!(sorted, vec!["..P", ".N", "R", "g", "M", "M'", "S."]);
+        assert!(pitch("g") < pitch("g+10"));
+        assert_eq!(pitch("D"), Pitch::of(Degree::Dha, Variant::Shuddh, 0).unwrap());
+    }
+
+    /// test intervals between pitches, and pitches transposed by them
+    #[test]
+    fn test_pitch_intervals() {
+        assert_eq!(pitch("S").interval(&pitch("P")), 7);
+        assert_eq!(pitch("S.").interval(&pitch(".N")), -13);
+        assert_eq!(pitch("S").transposed(6), pitch("M'"));
+        assert_eq!(pitch("P").transposed(5), pitch("S."));
+        assert_eq!(pitch(".D").transposed(-10), pitch("..N"));
+    }
+
+    /// test pitches shift by octaves only as far as ati-mandra and ati-taar
+    #[test]
+    fn test_pitch_shifted() {
+        assert_eq!(pitch("g+15").shifted(-2), Some(pitch("..g+15")));
+        assert_eq!(pitch("S.").higher(), Some(pitch("S..")));
+        assert_eq!(pitch("..P").lower(), None);
+        assert_eq!(pitch("N").shifted(3), None);
+    }
+
+    /// test a pitch off its swar sounds that many cents away
+    #[test]
+    fn test_pitch_cents() {
+        let (tonic, tuning) = (constants::tonic(), Tuning::default());
+        let sa = pitch("S").hertz(&tonic, &tuning).unwrap().freq();
+        let sharp = pitch("S+100").hertz(&tonic, &tuning).unwrap().freq();
+        assert!((sharp / sa - 2f64.powf(1.0 / 12.0)).abs() < 1e-4);
+    }
+
+    /// test a pitch sounds, and is named, relative to the Sa it is given
+    #[test]
+    fn test_pitch_hertz_from_sa() {
+        let d = "D".parse::<Hertz>().unwrap();
+        assert_eq!(pitch("P").hertz(&d, &Tuning::default()), Some(Hertz::new(440.0, "A".to_string())));
+        assert_eq!(pitch("P").hertz(&constants::tonic(), &Tuning::default()).unwrap().tone(), "G#");
+        assert_eq!(Pitch::from_semitones(36).hertz(&d, &Tuning::default()), None);
+    }
+}
@@ -1,132 +1,281 @@
 extern crate yaml_rust;
 
-use rodio::{decoder, Source};
 use yaml_rust::YamlLoader;
 
 use self::yaml_rust::{yaml, Yaml};
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::BufReader;
-use std::time::Duration;
-use crate::raagas::swars::BeatSrc;
-use crate::raagas::constants::BEAT_MP3;
-use crate::raagas::swarmaalika::{Sthayi, Antara, Swarmaalika};
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt;
+use std::path::PathBuf;
+use crate::raagas::swarmaalika::{Line, Sthayi, Antara, Swarmaalika};
 use crate::raagas::raag::raag::Raag;
 use crate::raagas::utils;
 use crate::raagas::swarblock::SwarBlock;
 use crate::raagas::swarblocks::SwarBlocks;
 use crate::raagas::aroha::Aroha;
 use crate::raagas::avroha::Avroha;
+use crate::raagas::pitch::{Degree, Pitch, Variant};
+use crate::raagas::tuning::{TuningOverride, TuningSystem, shruti_index};
+use crate::raagas::taal::Taal;
+use crate::raagas::laya::{Tempo, TempoMap};
+use crate::raagas::notation::{ParseError, Phrases};
+use crate::raagas::bols::Bols;
+use crate::raagas::beats::Beats;
+use crate::raagas::arrangement::{Arrangement, Part, Parts, Step};
+use crate::raagas::composition::{Alap, Composition, Form};
+
+// the keys of a composition, and those it can't do without
+const KEYS: [&str; 12] = ["phrases", "aroha", "avroha", "pakad", "alankars", "taal", "laya", "tuning", "form",
+                          "composition", "swarmaalika", "arrangement"];
+const REQUIRED: [&str; 3] = ["aroha", "avroha", "pakad"];
+
+/// A problem with a composition, and where in its yaml it is, e.g.
+/// `swarmaalika.sthayi.lineB`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    pub path: String,
+    pub message: String,
+}
 
-fn swar_line(doc: &Yaml) -> Option<SwarBlocks> {
-    let mut blk: Vec<SwarBlock> = Vec::new();
-    match doc {
-        yaml::Yaml::Array(ref v) => match v.get(0) {
-            Some(line) => {
-                if line.is_null() {
-                    None
-                } else {
-                    let blks_s: Vec<&str> = line.as_str().unwrap().split(",").collect();
-                    for blk_s in blks_s {
-                        blk.push(SwarBlock::from(blk_s));
-                    }
-                    Some(SwarBlocks(blk))
-                }
-            }
-            _ => None,
-        },
-        _ => None,
+impl Problem {
+    pub fn new(path: &str, message: &str) -> Self {
+        Problem {
+            path: path.to_string(),
+            message: message.to_string(),
+        }
     }
 }
 
-fn aroha_avroha(doc: &Yaml, comp: &str) -> Option<SwarBlocks> {
-    swar_line(&doc[comp])
+impl From<ParseError> for Problem {
+    /// Returns a typo in the notation as a problem on the line it is on
+    fn from(e: ParseError) -> Self {
+        let path = e.line.clone().unwrap_or_default();
+        Problem::new(&path, &ParseError { line: None, ..e }.to_string())
+    }
 }
 
-fn pakad(doc: &Yaml) -> Option<SwarBlocks> {
-    swar_line(&doc["pakad"])
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.path.as_str() {
+            "" => write!(f, "{}", self.message),
+            path => write!(f, "{}: {}", path, self.message),
+        }
+    }
 }
 
-fn alankars(doc: &Yaml) -> Option<SwarBlocks> {
-    swar_line(&doc["alankars"])
+/// The problems found loading a composition file, all of them rather than
+/// only the first
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadError {
+    pub file: PathBuf,
+    pub problems: Vec<Problem>,
 }
 
-fn sthayi(doc: &Yaml) -> Option<Sthayi> {
-    match doc {
-        yaml::Yaml::Array(ref v) => {
-            let parse = |i: usize, s: &str| {
-                let _y = v.get(i);
-                return match _y {
-                    Some(line) => swar_line(&line[s]),
-                    _ => None,
-                };
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid composition {}:", self.file.display())?;
+        for problem in &self.problems {
+            write!(f, "\n  {}", problem)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Error for LoadError {}
+
+// returns the value of `r`, noting its error as a problem at `path`
+fn check<T, E: fmt::Display>(problems: &mut Vec<Problem>, path: &str, r: Result<T, E>) -> Option<T> {
+    match r {
+        Ok(v) => Some(v),
+        Err(e) => {
+            problems.push(Problem::new(path, &e.to_string()));
+            None
+        },
+    }
+}
+
+/// Reads a line of swarblocks separated by `,`, e.g. the aroha, playing
+/// `phrases` where it refers to them; `line` names it in errors
+fn swar_line(doc: &Yaml, line: &str, phrases: &Phrases) -> Result<Option<SwarBlocks>, ParseError> {
+    let mut blk: Vec<SwarBlock> = Vec::new();
+    let s = match doc {
+        yaml::Yaml::Array(ref v) => match v.first().and_then(|l| l.as_str()) {
+            Some(s) => s,
+            _ => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+
+    let mut offset = 0;
+    for blk_s in s.split(',') {
+        let mut swarblk = SwarBlock(Vec::new());
+        swarblk.push_notation(blk_s, phrases)
+            .map_err(|e| e.shifted(offset).with_line(line))?;
+        blk.push(swarblk);
+        offset += blk_s.chars().count() + 1;
+    }
+
+    Ok(Some(SwarBlocks(blk)))
+}
+
+// reads the line of notation at `path`, if there is one, noting a typo in it
+// or anything other than a list starting with the notation as a problem
+fn notation_line(doc: &Yaml, path: &str, phrases: &Phrases, problems: &mut Vec<Problem>) -> Option<SwarBlocks> {
+    match swar_line(doc, path, phrases) {
+        Ok(Some(blks)) => Some(blks),
+        Ok(None) => {
+            // left blank, e.g. `- mukra:` or an empty `-` under it
+            let blank = match doc {
+                yaml::Yaml::BadValue | yaml::Yaml::Null => true,
+                yaml::Yaml::Array(v) => v.first().is_none_or(|l| l.is_null()),
+                _ => false,
             };
-            let mut lines: HashMap<String, SwarBlocks> = HashMap::new();
-            let tags: Vec<&str> = vec!["lineA", "lineB", "lineC"];
-
-            for t in tags {
-                for i in 0..v.len() {
-                    let line = parse(i, t);
-                    match line {
-                        Some(blk) => {
-                            lines.insert(t.to_string(), blk);
-                            break;
-                        }
-                        _ => {}
-                    }
-                }
+            if !blank {
+                problems.push(Problem::new(path, "should be a list starting with a line of notation"));
             }
+            None
+        },
+        Err(e) => {
+            problems.push(e.into());
+            None
+        },
+    }
+}
 
-            Some(Sthayi::new(lines))
+/// Reads the phrases of the composition, to be referred to as `$name` in its
+/// lines, e.g.:
+///
+/// phrases:
+///   mukhda: G:M P (D P)x2
+fn phrases(doc: &Yaml, problems: &mut Vec<Problem>) -> Phrases {
+    let mut phrases = Phrases::new();
+    match &doc["phrases"] {
+        yaml::Yaml::BadValue => {},
+        yaml::Yaml::Hash(h) => {
+            for (name, phrase) in h {
+                match (name.as_str(), phrase.as_str()) {
+                    (Some(name), Some(phrase)) => {
+                        phrases.insert(name.to_string(), phrase.to_string());
+                    },
+                    _ => problems.push(Problem::new("phrases", "a phrase should be a name and its notation")),
+                }
+            }
+        },
+        _ => problems.push(Problem::new("phrases", "should be a mapping of names to notation")),
+    }
+    // a phrase in invalid notation is reported where it is defined, as
+    // well as at the lines using it
+    let mut names: Vec<&String> = phrases.keys().collect();
+    names.sort();
+    for name in names {
+        if let Err(e) = SwarBlock(Vec::new()).push_notation(&phrases[name], &phrases) {
+            problems.push(e.with_line(&format!("phrases.{}", name)).into());
         }
-        _ => None,
     }
+
+    phrases
 }
 
-fn antara(doc: &Yaml) -> Option<Antara> {
-    match doc {
-        yaml::Yaml::Array(ref v) => {
-            let parse = |i: usize, s: &str| {
-                let _y = v.get(i);
-                return match _y {
-                    Some(line) => swar_line(&line[s]),
-                    _ => None,
-                };
-            };
+// returns the entries of `doc` by name, given either as a mapping or as a
+// list of mappings in any order, e.g. `- sthayi: ...`, noting any not
+// `expected` or given twice as problems
+fn entries<'a>(doc: &'a Yaml,
+               path: &str,
+               expected: &[&str],
+               problems: &mut Vec<Problem>) -> Vec<(&'a str, &'a Yaml)> {
+    let given: Vec<(&Yaml, &Yaml)> = match doc {
+        yaml::Yaml::Hash(h) => h.iter().collect(),
+        yaml::Yaml::Array(v) if v.iter().all(|e| e.as_hash().is_some()) => {
+            v.iter().flat_map(|e| e.as_hash().unwrap().iter()).collect()
+        },
+        _ => {
+            problems.push(Problem::new(path, &format!("should be a mapping of {}", expected.join(", "))));
+            return Vec::new();
+        },
+    };
+
+    let mut entries: Vec<(&str, &Yaml)> = Vec::new();
+    for (name, entry) in given {
+        let name = name.as_str().unwrap_or_default();
+        if !expected.contains(&name) {
+            let message = format!("unknown, expected one of {}", expected.join(", "));
+            problems.push(Problem::new(&format!("{}.{}", path, name), &message));
+        } else if entries.iter().any(|(n, _)| *n == name) {
+            problems.push(Problem::new(&format!("{}.{}", path, name), "given more than once"));
+        } else {
+            entries.push((name, entry));
+        }
+    }
 
-            let mut lines: HashMap<String, SwarBlocks> = HashMap::new();
-            let tags: Vec<&str> = vec!["lineC", "lineD", "lineE"];
+    entries
+}
 
-            for t in tags {
-                for i in 0..v.len() {
-                    let line = parse(i, t);
-                    match line {
-                        Some(blk) => {
-                            lines.insert(t.to_string(), blk);
-                            break;
+// reads the lines of the section of a composition at `path` in the order they
+// are given, each labelled or not, with the bols sung against it given under
+// its notation, e.g.:
+//
+// - lineA:
+//   - N D P - - R - S
+//   - e ri aa - - li - pi
+// - - G R G G - - P P
+fn section(doc: &Yaml, path: &str, phrases: &Phrases, problems: &mut Vec<Problem>) -> Vec<Line> {
+    // each line given, with its label (if any) and its path
+    let mut given: Vec<(Option<&str>, String, Yaml)> = Vec::new();
+    match doc {
+        yaml::Yaml::Hash(h) => {
+            for (label, line) in h {
+                let label = label.as_str().unwrap_or_default();
+                given.push((Some(label), format!("{}.{}", path, label), line.clone()));
+            }
+        },
+        yaml::Yaml::Array(v) => {
+            for (i, entry) in v.iter().enumerate() {
+                match entry {
+                    yaml::Yaml::Hash(h) => {
+                        for (label, line) in h {
+                            let label = label.as_str().unwrap_or_default();
+                            given.push((Some(label), format!("{}.{}", path, label), line.clone()));
                         }
-                        _ => {}
-                    }
+                    },
+                    // a line of notation on its own
+                    yaml::Yaml::String(_) => given.push((None, format!("{}[{}]", path, i), Yaml::Array(vec![entry.clone()]))),
+                    _ => given.push((None, format!("{}[{}]", path, i), entry.clone())),
                 }
             }
+        },
+        _ => problems.push(Problem::new(path, "should be a list of its lines")),
+    }
 
-            Some(Antara::new(lines))
+    let mut lines: Vec<Line> = Vec::new();
+    for (label, path, line) in given {
+        if label.is_some() && lines.iter().any(|l| l.label() == label) {
+            problems.push(Problem::new(&path, "given more than once"));
+            continue;
         }
-        _ => None,
+        let blks = match notation_line(&line, &path, phrases, problems) {
+            Some(blks) => blks,
+            _ => continue,
+        };
+        let mut section_line = Line::new(blks);
+        if let Some(label) = label {
+            section_line = section_line.with_label(label);
+        }
+        if let Some(bols) = line[1].as_str() {
+            let with_bols = bols.parse::<Bols>().and_then(|b| section_line.clone().with_bols(b));
+            if let Some(with_bols) = check(problems, &format!("{}[1]", path), with_bols) {
+                section_line = with_bols;
+            }
+        }
+        lines.push(section_line);
     }
-}
 
-fn mukra(doc: &Yaml) -> Option<SwarBlocks> {
-    swar_line(doc)
-}
-
-fn tihayi(doc: &Yaml) -> Option<SwarBlocks> {
-    swar_line(doc)
+    lines
 }
 
 fn parse_usize(doc: &Yaml) -> Option<usize> {
     match doc {
-        yaml::Yaml::Integer(ref n) => {
+        yaml::Yaml::Integer(ref n) if *n >= 1 => {
             let _n: usize = *n as usize;
             Some(_n)
         }
@@ -134,79 +283,353 @@ fn parse_usize(doc: &Yaml) -> Option<usize> {
     }
 }
 
-fn swarmaalika(doc: &Yaml) -> Option<Swarmaalika> {
-    let swarmaalika = &doc["swarmaalika"];
-    match swarmaalika {
-        yaml::Yaml::Array(ref v) => {
-            let sam_yaml = v.get(0).unwrap();
-            let sam_s = &sam_yaml["sam"];
-            let sam = parse_usize(sam_s);
+/// Reads the sections of a composition in `form` set to a taal, given under
+/// `key` in any order either as a mapping or as a list, e.g.:
+///
+/// swarmaalika:
+///   sam: 9
+///   sthayi:
+///     lineA:
+///       - N D P - - R - S
+///   antara:
+///     ...
+fn swarmaalika(doc: &Yaml,
+               key: &str,
+               form: Form,
+               phrases: &Phrases,
+               problems: &mut Vec<Problem>) -> Option<Swarmaalika> {
+    let before = problems.len();
+    let sections: HashMap<&str, &Yaml> = entries(&doc[key], key, form.sections(), problems)
+        .into_iter()
+        .collect();
+    let section_of = |name: &str| sections.get(name).copied().unwrap_or(&Yaml::BadValue);
+    let path = |name: &str| format!("{}.{}", key, name);
+
+    let sam = match section_of("sam") {
+        yaml::Yaml::BadValue => None,
+        s => {
+            let sam = parse_usize(s);
+            if sam.is_none() {
+                problems.push(Problem::new(&path("sam"), "should be the beat falling on sam, from 1"));
+            }
+            sam
+        },
+    };
+    let mukra = notation_line(section_of("mukra"), &path("mukra"), phrases, problems);
+    let sthayi = match sections.get("sthayi") {
+        Some(s) => Sthayi::new(section(s, &path("sthayi"), phrases, problems)),
+        _ => {
+            problems.push(Problem::new(&path("sthayi"), "missing"));
+            Sthayi::new(Vec::new())
+        },
+    };
+    let antara = match section_of("antara") {
+        yaml::Yaml::BadValue | yaml::Yaml::Null => Antara::new(Vec::new()),
+        a => Antara::new(section(a, &path("antara"), phrases, problems)),
+    };
+    // the taans of a khayal or tarana, or the todas of a gat
+    let taans = match ["taans", "todas"].iter().find(|name| sections.contains_key(*name)) {
+        Some(name) => section(section_of(name), &path(name), phrases, problems),
+        _ => Vec::new(),
+    };
+    let tihayi = notation_line(section_of("tihayi"), &path("tihayi"), phrases, problems);
+
+    if problems.len() > before {
+        return None;
+    }
 
-            let mukra_yaml = v.get(1).unwrap();
-            let mukra_s = &mukra_yaml["mukra"];
-            let mukra = mukra(mukra_s);
+    Some(Swarmaalika::new(mukra, sthayi, antara, tihayi, sam).with_taans(taans))
+}
 
-            let sthayi_yaml = v.get(2).unwrap();
-            let sthayi_s = &sthayi_yaml["sthayi"];
-            let sthayi: Sthayi = sthayi(sthayi_s).unwrap();
+/// Reads the sections of an alap given under `key`, each a list of lines
+/// played without a taal, e.g.:
+///
+/// composition:
+///   alap:
+///     - .N - R - G - - -
+///   jor:
+///     - ...
+///   jhala:
+///     - ...
+fn alap(doc: &Yaml, key: &str, phrases: &Phrases, problems: &mut Vec<Problem>) -> Option<Alap> {
+    let before = problems.len();
+    let sections: HashMap<&str, &Yaml> = entries(&doc[key], key, Form::Alap.sections(), problems)
+        .into_iter()
+        .collect();
+    if !sections.contains_key("alap") {
+        problems.push(Problem::new(&format!("{}.alap", key), "missing"));
+    }
+    let mut section_of = |name: &str| match sections.get(name) {
+        Some(s) => section(s, &format!("{}.{}", key, name), phrases, problems),
+        _ => Vec::new(),
+    };
+    let alap = Alap::new(section_of("alap"), section_of("jor"), section_of("jhala"));
 
-            let antara_yaml = v.get(3).unwrap();
-            let antara_s = &antara_yaml["antara"];
-            let antara: Antara = antara(antara_s).unwrap();
+    if problems.len() > before {
+        return None;
+    }
 
-            let tihayi_yaml = v.get(4).unwrap();
-            let tihayi_s = &tihayi_yaml["tihayi"];
-            let tihayi = tihayi(tihayi_s);
+    Some(alap)
+}
 
-            Some(Swarmaalika::new(mukra, sthayi, antara, tihayi, sam))
-        }
-        _ => None,
+// reads the form of the composition, a swarmaalika if not given
+fn form(doc: &Yaml) -> Result<Form, String> {
+    match &doc["form"] {
+        yaml::Yaml::BadValue => Ok(Form::default()),
+        yaml::Yaml::String(s) => s.parse::<Form>(),
+        _ => Err("should be the name of a form".to_string()),
     }
 }
 
-fn play_raw_beats_forever(beatp: (&str, f32)) -> BeatSrc {
-    let f = File::open(beatp.0).expect(&format!("Unable to open file {}", beatp.0));
-    let source = decoder::Decoder::new(BufReader::new(f)).unwrap();
-    // we are having to do this as the total_duration is returned none for
-    // wav, mp3 files in some cases.
-    let t = match source.total_duration() {
-        Some(_t) => _t,
-        _ => Duration::from_secs_f32(beatp.1),
+/// Reads the raag's adjustments to the tuning, e.g.:
+///
+/// tuning:
+///   system: shruti
+///   shrutis:
+///     r: ati-komal re
+fn tuning(doc: &Yaml, problems: &mut Vec<Problem>) -> Option<TuningOverride> {
+    let tuning = &doc["tuning"];
+    if tuning.is_badvalue() {
+        return None;
+    }
+
+    let system = match &tuning["system"] {
+        yaml::Yaml::BadValue => None,
+        s => check(problems, "tuning.system", s.as_str().unwrap_or_default().parse::<TuningSystem>()),
     };
 
-    let beat_src = source.take_duration(t).repeat_infinite();
+    let mut shrutis: BTreeMap<(Degree, Variant), usize> = BTreeMap::new();
+    match &tuning["shrutis"] {
+        yaml::Yaml::BadValue => {},
+        yaml::Yaml::Hash(h) => {
+            for (swar, shruti) in h {
+                let swar = match swar {
+                    yaml::Yaml::Integer(n) => n.to_string(),
+                    s => s.as_str().unwrap_or_default().to_string(),
+                };
+                let path = format!("tuning.shrutis.{}", swar);
+                let pitch = match swar.parse::<Pitch>() {
+                    Ok(p) if p.octave() == 0 && p.cents() == 0 => Some(p),
+                    _ => {
+                        problems.push(Problem::new(&path, "should be a swar of the madhya saptak, e.g. r"));
+                        None
+                    },
+                };
+                let i = match shruti {
+                    yaml::Yaml::Integer(n) => check(problems, &path, shruti_index(&n.to_string())),
+                    yaml::Yaml::String(s) => check(problems, &path, shruti_index(s)),
+                    _ => {
+                        problems.push(Problem::new(&path, "should be a shruti, by name or number"));
+                        None
+                    },
+                };
+                if let (Some(pitch), Some(i)) = (pitch, i) {
+                    shrutis.insert((pitch.degree(), pitch.variant()), i);
+                }
+            }
+        },
+        _ => problems.push(Problem::new("tuning.shrutis", "should be a mapping of swars to shrutis")),
+    }
 
-    beat_src
+    Some(TuningOverride { system, shrutis })
 }
 
-pub fn load_yaml(raag: &str, composition: &str) -> Option<Raag> {
-    // Given a raag name returns a Raag
-    let s = utils::read_composition_as_str(raag, composition);
-    let yamlldr = YamlLoader::load_from_str(&s);
-    match &yamlldr {
-        Ok(docs) => {
-            let doc = &docs[0];
-            let aroha = aroha_avroha(&doc, "aroha")?;
-            let avroha = aroha_avroha(&doc, "avroha")?;
-            let pakad = pakad(&doc)?;
-            let alankars = match alankars(&doc) {
-                Some(v) => Some(v),
-                _ => None,
+/// Reads the taal the composition is set to, e.g. `taal: teentaal`, checking
+/// the composition's `sam` falls within the taal
+fn taal(doc: &Yaml, sam: usize) -> Result<Option<Taal>, String> {
+    let name = match &doc["taal"] {
+        yaml::Yaml::String(name) => name,
+        yaml::Yaml::BadValue => return Ok(None),
+        _ => return Err("taal should be the name of a taal".to_string()),
+    };
+
+    let taal = Taal::load(&name.to_lowercase())?;
+    if sam > taal.matras() {
+        return Err(format!("sam {} is beyond the {} matras of {}", sam, taal.matras(), taal.name()));
+    }
+
+    Ok(Some(taal))
+}
+
+// reads a tempo in beats per minute, e.g. `90` or `80-120`, or a laya
+fn tempo(doc: &Yaml) -> Result<Tempo, String> {
+    match doc {
+        yaml::Yaml::String(s) => s.parse::<Tempo>(),
+        yaml::Yaml::Integer(n) => n.to_string().parse::<Tempo>(),
+        yaml::Yaml::Real(r) => r.parse::<Tempo>(),
+        _ => Err("tempo should be in beats per minute or a laya".to_string()),
+    }
+}
+
+/// Reads the tempo the swarmaalika is played at, either all of it (e.g.
+/// `laya: madhya`) or section by section, e.g.:
+///
+/// laya:
+///   sthayi: 80-90
+///   antara: 90-110
+///   tihayi: drut
+fn laya(doc: &Yaml) -> Result<TempoMap, String> {
+    let mut tempos = TempoMap::new();
+    match &doc["laya"] {
+        yaml::Yaml::BadValue => {},
+        yaml::Yaml::Hash(h) => {
+            for (section, t) in h {
+                tempos.insert(section.as_str().unwrap_or_default(), tempo(t)?)?;
+            }
+        },
+        t => tempos.insert("sthayi", tempo(t)?)?,
+    }
+
+    Ok(tempos)
+}
+
+// reads a step of the arrangement: a part on its own, or with the times it
+// is played (the beats, for a pause) or with its times and tempo
+fn step(doc: &Yaml) -> Result<Step, String> {
+    let (part, how) = match doc {
+        yaml::Yaml::String(s) => return Ok(Step::new(s.parse::<Part>()?)),
+        yaml::Yaml::Hash(h) if h.len() == 1 => h.front().unwrap(),
+        _ => return Err("should be a part, or a part and the times it is played".to_string()),
+    };
+    let part = part.as_str().unwrap_or_default().parse::<Part>()?;
+    let times = |n: &Yaml| parse_usize(n).ok_or_else(|| "times should be a whole number from 1".to_string());
+    match (part, how) {
+        (Part::Pause(_), yaml::Yaml::Integer(_) | yaml::Yaml::String(_)) => {
+            let beats = match how {
+                yaml::Yaml::Integer(n) => Beats::whole(*n),
+                _ => how.as_str().unwrap_or_default().parse::<Beats>()?,
             };
-            let swarmaalika = swarmaalika(&doc)?;
-            let beat_src = play_raw_beats_forever(BEAT_MP3);
+            Ok(Step::new(Part::Pause(beats)))
+        },
+        (part, yaml::Yaml::Integer(_)) => Ok(Step::new(part).with_times(times(how)?)),
+        (part, yaml::Yaml::Hash(h)) => {
+            let mut step = Step::new(part);
+            for (key, value) in h {
+                step = match key.as_str().unwrap_or_default() {
+                    "times" => step.with_times(times(value)?),
+                    "tempo" => step.with_tempo(tempo(value)?),
+                    key => return Err(format!("{} is not times or tempo", key)),
+                };
+            }
+            Ok(step)
+        },
+        (part, _) => Err(format!("{} should be given the times it is played, or its times and tempo", part)),
+    }
+}
+
+/// Reads the parts of the raag in the order they are played, e.g.:
+///
+/// arrangement:
+///   - aroha
+///   - pause: 4
+///   - mukra
+///   - sthayi: 2
+///   - sthayi lineA
+///   - antara: { times: 2, tempo: 90-120 }
+///   - tihayi
+fn arrangement(doc: &Yaml, composition: Option<&Composition>, problems: &mut Vec<Problem>) -> Option<Arrangement> {
+    let steps = match &doc["arrangement"] {
+        yaml::Yaml::BadValue => return None,
+        yaml::Yaml::Array(v) => v,
+        _ => {
+            problems.push(Problem::new("arrangement", "should be a list of the parts played"));
+            return None;
+        },
+    };
+
+    // the steps read, with where each is in the list
+    let mut read: Vec<(usize, Step)> = Vec::new();
+    for (i, doc) in steps.iter().enumerate() {
+        if let Some(step) = check(problems, &format!("arrangement[{}]", i), step(doc)) {
+            read.push((i, step));
+        }
+    }
+    let (at, steps): (Vec<usize>, Vec<Step>) = read.into_iter().unzip();
+    let arrangement = Arrangement(steps);
+    if let Some(composition) = composition {
+        for (i, message) in arrangement.check(&Parts::from(composition)) {
+            problems.push(Problem::new(&format!("arrangement[{}]", at[i]), &message));
+        }
+    }
 
-            Some(Raag::new(
+    Some(arrangement)
+}
+
+// reads a composition, noting every problem with it rather than stopping at
+// the first
+fn composition(raag: &str, doc: &Yaml) -> Result<Raag, Vec<Problem>> {
+    let mut problems = Vec::new();
+    match doc.as_hash() {
+        Some(h) => {
+            for key in h.keys() {
+                let key = key.as_str().unwrap_or_default();
+                if !KEYS.contains(&key) {
+                    let message = format!("unknown, expected one of {}", KEYS.join(", "));
+                    problems.push(Problem::new(key, &message));
+                }
+            }
+        },
+        _ => return Err(vec![Problem::new("", "a composition should be a mapping of its aroha, avroha, ...")]),
+    }
+    let form = check(&mut problems, "form", form(doc));
+    // a swarmaalika can be given under `swarmaalika`, as it always has been,
+    // and a composition in any form under `composition`
+    let key = match form {
+        Some(Form::Swarmaalika) | None if doc["composition"].is_badvalue() => "swarmaalika",
+        _ => "composition",
+    };
+    for key in REQUIRED.iter().chain([key].iter()).filter(|key| doc[**key].is_badvalue()) {
+        problems.push(Problem::new(key, "missing"));
+    }
+    if key != "swarmaalika" && !doc["swarmaalika"].is_badvalue() {
+        problems.push(Problem::new("swarmaalika", "should be given under composition, with its form"));
+    }
+
+    let phrases = phrases(doc, &mut problems);
+    let aroha = notation_line(&doc["aroha"], "aroha", &phrases, &mut problems);
+    let avroha = notation_line(&doc["avroha"], "avroha", &phrases, &mut problems);
+    let pakad = notation_line(&doc["pakad"], "pakad", &phrases, &mut problems);
+    let alankars = notation_line(&doc["alankars"], "alankars", &phrases, &mut problems);
+    let composition = match (&doc[key], form.unwrap_or_default()) {
+        (yaml::Yaml::BadValue, _) => None,
+        (_, Form::Alap) => alap(doc, key, &phrases, &mut problems).map(Composition::Alap),
+        (_, form) => swarmaalika(doc, key, form, &phrases, &mut problems).and_then(|s| Composition::new(form, s)),
+    };
+    let sam = composition.as_ref().map_or(1, |c| c.sam());
+    let taal = check(&mut problems, "taal", taal(doc, sam));
+    if let (Some(Form::Alap), Some(Some(_))) = (form, &taal) {
+        problems.push(Problem::new("taal", "an alap isn't set to a taal"));
+    }
+    let tempos = check(&mut problems, "laya", laya(doc));
+    let tuning = tuning(doc, &mut problems);
+    let arrangement = arrangement(doc, composition.as_ref(), &mut problems);
+
+    match (aroha, avroha, pakad, composition, taal, tempos) {
+        (Some(aroha), Some(avroha), Some(pakad), Some(composition), Some(taal), Some(tempos))
+            if problems.is_empty() => {
+            Ok(Raag::new(
                 raag.to_string(),
                 Aroha::new(aroha),
                 Avroha::new(avroha),
                 Some(pakad),
                 alankars,
-                swarmaalika,
-                Some(beat_src),
-            ))
-        }
-        _ => None,
+                composition.with_taal(taal).with_tempos(tempos),
+            ).with_tuning(tuning).with_arrangement(arrangement))
+        },
+        _ => Err(problems),
+    }
+}
+
+/// Loads `composition` of `raag` from its yaml file, or every problem found
+/// with it
+pub fn load_yaml(raag: &str, composition: &str) -> Result<Raag, LoadError> {
+    let file = utils::composition_path(raag, composition);
+    let error = |problems| LoadError { file: file.clone(), problems };
+    let s = std::fs::read_to_string(&file).map_err(|e| error(vec![Problem::new("", &e.to_string())]))?;
+    let docs = YamlLoader::load_from_str(&s).map_err(|e| error(vec![Problem::new("", &e.to_string())]))?;
+    match docs.first() {
+        Some(doc) => self::composition(raag, doc).map_err(error),
+        _ => Err(error(vec![Problem::new("", "empty")])),
     }
 }
 
@@ -215,6 +638,15 @@ mod tests {
     use crate::raagas::raag::load;
     use crate::raagas::utils;
     use super::yaml_rust::YamlLoader;
+    use crate::raagas::tuning::TuningSystem;
+    use crate::raagas::pitch::{Degree, Variant};
+    use crate::raagas::laya::{Laya, Tempo};
+    use crate::raagas::constants::CONF_DIR;
+    use crate::raagas::taal::TAALS_DIR;
+    use crate::raagas::notation::{ParseErrorKind, Phrases};
+    use crate::raagas::beats::Beats;
+    use crate::raagas::arrangement::{Arrangement, Part, Step};
+    use crate::raagas::composition::Form;
 
     /// load a raag from yaml
     #[test]
@@ -222,7 +654,62 @@ mod tests {
         let raag = "bhupali";
         let composition = "1";
         let raag = load::load_yaml(raag, composition);
-        assert!(raag.is_some());
+        assert!(raag.is_ok());
+    }
+
+    /// test a composition that can't be read names its file
+    #[test]
+    fn test_load_yaml_missing() {
+        let e = load::load_yaml("yaman", "no_such_bandish").err().unwrap();
+        assert_eq!(e.file, std::path::Path::new("./config/yaman/no_such_bandish.yaml"));
+        assert!(e.to_string().starts_with("Invalid composition ./config/yaman/no_such_bandish.yaml:\n  "));
+    }
+
+    /// test every problem with a composition is reported at once, each at
+    /// its path in the yaml
+    #[test]
+    fn test_composition_problems() {
+        let s = concat!("aroha:\n  - S R G\npakad: S R\ntaal: bhajani\nraga: yaman\n",
+                        "swarmaalika:\n  sam: 0\n  sthayi:\n    lineA:\n      - S X\n    lineZ:\n      - S\n",
+                        "  antara:\n    lineC:\n      - S R - G\n      - ga ri - ma ni\n  tihayi:\n    - P $mukhda\n");
+        let docs = YamlLoader::load_from_str(s).unwrap();
+        let problems: Vec<String> = load::composition("yaman", &docs[0]).err().unwrap()
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        let expected = [
+            "raga: unknown, expected one of phrases, aroha, avroha, pakad, alankars, taal, laya, tuning, form, composition, swarmaalika, arrangement",
+            "avroha: missing",
+            "pakad: should be a list starting with a line of notation",
+            "swarmaalika.sam: should be the beat falling on sam, from 1",
+            "swarmaalika.sthayi.lineA: column 3: unknown swar at \"X\"",
+            "swarmaalika.antara.lineC[1]: 5 syllables for 4 swarbeats",
+            "swarmaalika.tihayi: column 3: unknown phrase at \"$mukhda\"",
+            "taal: Taal bhajani not found, taals available: teentaal,ektaal,jhaptaal,rupak,dadra,keherwa",
+        ];
+        assert_eq!(problems, expected);
+    }
+
+    /// test the sections of a swarmaalika can be given in any order, as a
+    /// mapping or as a list, and its lines are kept in the order given
+    #[test]
+    fn test_swarmaalika_sections() {
+        let s = concat!("swarmaalika:\n  tihayi:\n    - P - S\n  antara:\n    lineC:\n      - P S.\n",
+                        "  sthayi:\n    lineB:\n      - G M\n    lineA:\n      - S R\n  sam: 2\n");
+        let docs = YamlLoader::load_from_str(s).unwrap();
+        let mut problems = Vec::new();
+        let swarmaalika = load::swarmaalika(&docs[0], "swarmaalika", Form::Swarmaalika, &Phrases::new(), &mut problems).unwrap();
+        assert_eq!(swarmaalika.sam(), 2);
+        let labels: Vec<Option<&str>> = swarmaalika.sthayi.lines.iter().map(|l| l.label()).collect();
+        assert_eq!(labels, vec![Some("lineB"), Some("lineA")]);
+        assert_eq!(swarmaalika.sthayi.line("lineB").unwrap().blks.to_string(), "G M");
+        assert_eq!(swarmaalika.tihayi.unwrap().to_string(), "P - S");
+
+        let s = "swarmaalika:\n  - sthayi:\n    - lineA:\n      - S R\n  - sam: 9\n  - sam: 1\n";
+        let docs = YamlLoader::load_from_str(s).unwrap();
+        let swarmaalika = load::swarmaalika(&docs[0], "swarmaalika", Form::Swarmaalika, &Phrases::new(), &mut problems);
+        assert!(swarmaalika.is_none());
+        assert_eq!(problems, vec![load::Problem::new("swarmaalika.sam", "given more than once")]);
     }
 
     /// test reading a line from yaml
@@ -230,13 +717,13 @@ mod tests {
     fn test_swar_line_aroha() {
         let raag = "bhupali";
         let composition = "1";
-        let composition_s = utils::read_composition_as_str(raag, composition);
+        let composition_s = std::fs::read_to_string(utils::composition_path(raag, composition)).unwrap();
         let yamlldr = YamlLoader::load_from_str(&composition_s);
         match &yamlldr {
             Ok(docs) => {
                 let doc = &docs[0];
                 let aroha = &doc["aroha"];
-                let aroha_blks = load::swar_line(aroha);
+                let aroha_blks = load::swar_line(aroha, "aroha", &Phrases::new()).unwrap();
                 assert!(aroha_blks.is_some());
             },
             _ => {}
@@ -244,4 +731,231 @@ mod tests {
 
     }
 
-}
\ No newline at end of file
+    /// test a typo in the notation is reported with its line and column
+    #[test]
+    fn test_swar_line_error() {
+        let docs = YamlLoader::load_from_str("pakad:\n  - S R, G X\n").unwrap();
+        let e = load::swar_line(&docs[0]["pakad"], "pakad", &Phrases::new()).unwrap_err();
+        assert_eq!(e.line, Some("pakad".to_string()));
+        assert_eq!((e.token.as_str(), e.column), ("X", 8));
+
+        let s = "swarmaalika:\n  - sam: 1\n  - mukra:\n  - sthayi:\n    - lineB:\n      - S R:G:Q P\n";
+        let docs = YamlLoader::load_from_str(s).unwrap();
+        let mut problems = Vec::new();
+        assert!(load::swarmaalika(&docs[0], "swarmaalika", Form::Swarmaalika, &Phrases::new(), &mut problems).is_none());
+        assert_eq!(problems[0].to_string(), "swarmaalika.sthayi.lineB: column 7: unknown swar at \"Q\"");
+    }
+
+    /// test every composition in the config directory is in valid notation
+    #[test]
+    fn test_load_all_compositions() {
+        let mut loaded = 0;
+        for dir in std::fs::read_dir(CONF_DIR).unwrap().flatten() {
+            // the taals are read with `Taal::load`
+            if !dir.path().is_dir() || dir.file_name() == TAALS_DIR {
+                continue;
+            }
+            let raag = dir.file_name().into_string().unwrap();
+            for entry in std::fs::read_dir(dir.path()).unwrap().flatten() {
+                let path = entry.path();
+                if path.extension().is_none_or(|ext| ext != "yaml") {
+                    continue;
+                }
+                let composition = path.file_stem().unwrap().to_str().unwrap();
+                if let Err(e) = load::load_yaml(&raag, composition) {
+                    panic!("{}", e);
+                }
+                loaded += 1;
+            }
+        }
+        assert_eq!(loaded, 7);
+    }
+
+    /// test reading a raag's tuning adjustments from yaml
+    #[test]
+    fn test_tuning() {
+        let s = "tuning:\n  system: just\n  shrutis:\n    r: ati-komal re\n    d: 14\n";
+        let docs = YamlLoader::load_from_str(s).unwrap();
+        let mut problems = Vec::new();
+        let tuning = load::tuning(&docs[0], &mut problems).unwrap();
+        assert!(problems.is_empty());
+        assert_eq!(tuning.system, Some(TuningSystem::JustIntonation));
+        assert_eq!(tuning.shrutis.get(&(Degree::Re, Variant::Komal)), Some(&1));
+        assert_eq!(tuning.shrutis.get(&(Degree::Dha, Variant::Komal)), Some(&14));
+
+        // every bad entry is a problem of its own
+        let s = "tuning:\n  system: pythagorean\n  shrutis:\n    X: 1\n    r: 22\n    g: komal ga\n";
+        let docs = YamlLoader::load_from_str(s).unwrap();
+        let mut problems = Vec::new();
+        load::tuning(&docs[0], &mut problems);
+        let paths: Vec<&str> = problems.iter().map(|p| p.path.as_str()).collect();
+        assert_eq!(paths, vec!["tuning.system", "tuning.shrutis.X", "tuning.shrutis.r"]);
+        let docs = YamlLoader::load_from_str("aroha:\n  - S R G\n").unwrap();
+        assert_eq!(load::tuning(&docs[0], &mut problems), None);
+    }
+
+    /// test reading the taal of a composition, whose sam must fall within it
+    #[test]
+    fn test_taal() {
+        let docs = YamlLoader::load_from_str("taal: Jhaptaal\n").unwrap();
+        let taal = load::taal(&docs[0], 1).unwrap().unwrap();
+        assert_eq!(taal.matras(), 10);
+        assert!(load::taal(&docs[0], 11).is_err());
+
+        let docs = YamlLoader::load_from_str("taal: bhajani\n").unwrap();
+        assert!(load::taal(&docs[0], 1).is_err());
+        let docs = YamlLoader::load_from_str("aroha:\n  - S R G\n").unwrap();
+        assert_eq!(load::taal(&docs[0], 1), Ok(None));
+    }
+
+    /// test reading the tempo of the whole swarmaalika or of each section
+    #[test]
+    fn test_laya() {
+        let docs = YamlLoader::load_from_str("laya:\n  sthayi: 80\n  antara: 90-120\n  tihayi: drut\n").unwrap();
+        let tempos = load::laya(&docs[0]).unwrap();
+        assert_eq!(tempos.get("sthayi"), Some(&Tempo::new(80.0)));
+        assert_eq!(tempos.get("antara"), Some(&Tempo::ramp(90.0, 120.0)));
+        assert_eq!(tempos.get("tihayi"), Some(&Tempo::from(Laya::Drut)));
+
+        let docs = YamlLoader::load_from_str("laya: vilambit\n").unwrap();
+        assert_eq!(load::laya(&docs[0]).unwrap().get("sthayi"), Some(&Tempo::new(40.0)));
+        let docs = YamlLoader::load_from_str("laya:\n  pakad: drut\n").unwrap();
+        assert!(load::laya(&docs[0]).is_err());
+        let docs = YamlLoader::load_from_str("laya: fast\n").unwrap();
+        assert!(load::laya(&docs[0]).is_err());
+        let docs = YamlLoader::load_from_str("aroha:\n  - S R G\n").unwrap();
+        assert!(load::laya(&docs[0]).unwrap().is_empty());
+    }
+
+    /// test phrases are played where lines refer to them, and a phrase in
+    /// invalid notation is reported where it is defined
+    #[test]
+    fn test_phrases() {
+        let s = "phrases:\n  mukhda: G:M P\npakad:\n  - (S R)x2, $mukhda -\n";
+        let docs = YamlLoader::load_from_str(s).unwrap();
+        let mut problems = Vec::new();
+        let phrases = load::phrases(&docs[0], &mut problems);
+        assert!(problems.is_empty());
+        let pakad = load::swar_line(&docs[0]["pakad"], "pakad", &phrases).unwrap().unwrap();
+        assert_eq!(pakad.to_string(), "S R S R G:M P -");
+
+        let e = load::swar_line(&docs[0]["pakad"], "pakad", &Phrases::new()).unwrap_err();
+        assert_eq!((e.kind, e.column), (ParseErrorKind::UnknownPhrase, 10));
+
+        let docs = YamlLoader::load_from_str("phrases:\n  mukhda: G:X P\n").unwrap();
+        load::phrases(&docs[0], &mut problems);
+        assert_eq!(problems[0].to_string(), "phrases.mukhda: column 3: unknown swar at \"X\"");
+    }
+
+    /// test a section can have any number of lines, labelled or not, each
+    /// played in turn
+    #[test]
+    fn test_section_lines() {
+        let s = concat!("swarmaalika:\n  - sthayi:\n    - lineA:\n      - S R\n",
+                        "  - antara:\n    - lineC:\n      - P S.\n    - - S. N\n      - sa ni\n",
+                        "    - D P\n    - lineF:\n      - M G\n    - lineC:\n      - R S\n");
+        let docs = YamlLoader::load_from_str(s).unwrap();
+        let mut problems = Vec::new();
+        assert!(load::swarmaalika(&docs[0], "swarmaalika", Form::Swarmaalika, &Phrases::new(), &mut problems).is_none());
+        assert_eq!(problems, vec![load::Problem::new("swarmaalika.antara.lineC", "given more than once")]);
+
+        let docs = YamlLoader::load_from_str(&s.replace("    - lineC:\n      - R S\n", "")).unwrap();
+        let mut problems = Vec::new();
+        let swarmaalika = load::swarmaalika(&docs[0], "swarmaalika", Form::Swarmaalika, &Phrases::new(), &mut problems).unwrap();
+        let antara = &swarmaalika.antara;
+        let names: Vec<String> = antara.lines.iter().enumerate().map(|(i, l)| l.name(i)).collect();
+        assert_eq!(names, vec!["lineC", "line 2", "line 3", "lineF"]);
+        assert_eq!(antara.lines[1].bols().unwrap().to_string(), "sa ni");
+        assert_eq!(antara.lines[2].blks.to_string(), "D P");
+        // each of the four lines is played twice
+        assert_eq!(antara.beats(), Beats::whole(16));
+    }
+
+    /// test reading the arrangement, each part on its own or with its times,
+    /// tempo or (for a pause) beats, and its lines checked against the
+    /// swarmaalika
+    #[test]
+    fn test_arrangement() {
+        let s = concat!("arrangement:\n  - mukra\n  - sthayi: 2\n  - pause: 3/2\n  - sthayi lineA\n",
+                        "  - antara: { times: 2, tempo: 90-120 }\n  - tihayi\n");
+        let docs = YamlLoader::load_from_str(s).unwrap();
+        let raag = load::load_yaml("yaman", "yeri_aali").unwrap();
+        let mut problems = Vec::new();
+        let arrangement = load::arrangement(&docs[0], Some(raag.composition()), &mut problems).unwrap();
+        assert!(problems.is_empty());
+        assert_eq!(arrangement, Arrangement(vec![
+            Step::new(Part::Mukra),
+            Step::new(Part::Sthayi).with_times(2),
+            Step::new(Part::Pause(Beats::new(3, 2))),
+            Step::new(Part::SthayiLine("lineA".to_string())),
+            Step::new(Part::Antara).with_times(2).with_tempo(Tempo::ramp(90.0, 120.0)),
+            Step::new(Part::Tihayi),
+        ]));
+
+        let s = "arrangement:\n  - thumri\n  - sthayi: 0\n  - antara: { speed: 2 }\n  - antara lineZ\n";
+        let docs = YamlLoader::load_from_str(s).unwrap();
+        let mut problems = Vec::new();
+        load::arrangement(&docs[0], Some(raag.composition()), &mut problems);
+        let paths: Vec<&str> = problems.iter().map(|p| p.path.as_str()).collect();
+        assert_eq!(paths, vec!["arrangement[0]", "arrangement[1]", "arrangement[2]", "arrangement[3]"]);
+        assert_eq!(problems[3].message, "no line antara lineZ");
+    }
+
+    /// test a composition in another form is read from `composition`, with
+    /// the sections of its form
+    #[test]
+    fn test_forms() {
+        let raag = "aroha:\n  - S R G\navroha:\n  - G R S\npakad:\n  - S R\n";
+        let read = |s: &str| {
+            let docs = YamlLoader::load_from_str(&format!("{}{}", raag, s)).unwrap();
+            load::composition("yaman", &docs[0])
+        };
+        let gat = read(concat!("taal: teentaal\nform: gat\ncomposition:\n  sthayi:\n    - S R G M\n",
+                               "  antara:\n    - P D N S.\n  todas:\n    - S:R G:M P:D N:S.\n  tihayi:\n    - S R G\n"));
+        let gat = gat.ok().unwrap();
+        assert_eq!(gat.composition().form(), Form::Gat);
+        assert_eq!(gat.swarmaalika().unwrap().taans[0].blks.to_string(), "S:R G:M P:D N:S.");
+
+        let alap = "form: alap\ncomposition:\n  alap:\n    - .N - R - G - -\n  jhala:\n    - S S S S\n";
+        let problems = read(&format!("taal: teentaal\n{}", alap)).err().unwrap();
+        assert_eq!(problems, vec![load::Problem::new("taal", "an alap isn't set to a taal")]);
+        let alap = read(alap).ok().unwrap();
+        assert!(alap.swarmaalika().is_none());
+        let alap = alap.composition().alap().unwrap();
+        assert!(alap.jor.is_empty());
+        assert_eq!(alap.tempo("jhala"), Some(&Tempo::from(Laya::Drut)));
+
+        let problems: Vec<String> = read("form: lakshan geet\ncomposition:\n  sthayi:\n    - S R\n  tihayi:\n    - S\n")
+            .err().unwrap()
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        assert_eq!(problems, vec!["composition.tihayi: unknown, expected one of sam, mukra, sthayi, antara"]);
+        let problems = read("form: tarana\nswarmaalika:\n  sthayi:\n    - S R\n").err().unwrap();
+        assert_eq!(problems, vec![
+            load::Problem::new("composition", "missing"),
+            load::Problem::new("swarmaalika", "should be given under composition, with its form"),
+        ]);
+        let problems = read("composition:\n  antara:\n    - S R\n").err().unwrap();
+        assert_eq!(problems, vec![load::Problem::new("composition.sthayi", "missing")]);
+    }
+
+    /// test reading the bols sung against the lines of a bandish, which must
+    /// fit their swarbeats
+    #[test]
+    fn test_bols() {
+        let raag = load::load_yaml("yaman", "yeri_aali").unwrap();
+        let sthayi = &raag.swarmaalika().unwrap().sthayi;
+        let bols = sthayi.line("lineA").unwrap().bols().unwrap();
+        let blks = &sthayi.line("lineA").unwrap().blks;
+        assert_eq!(bols.pairs(blks)[5].0.to_string(), "R");
+        assert_eq!(bols.pairs(blks)[5].1, "li");
+
+        let s = concat!("swarmaalika:\n  - sam: 1\n  - mukra:\n  - sthayi:\n    - lineA:\n",
+                        "      - S R - G\n      - ga ri - ma ni\n  - antara:\n  - tihayi:\n");
+        let docs = YamlLoader::load_from_str(s).unwrap();
+        let mut problems = Vec::new();
+        assert!(load::swarmaalika(&docs[0], "swarmaalika", Form::Swarmaalika, &Phrases::new(), &mut problems).is_none());
+        assert_eq!(problems[0].to_string(), "swarmaalika.sthayi.lineA[1]: 5 syllables for 4 swarbeats");
+    }
+}
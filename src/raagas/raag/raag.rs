@@ -1,21 +1,31 @@
 use crate::raagas::swarmaalika::Swarmaalika;
-use crate::raagas::sound::{AudioDevice};
-use crate::raagas::constants::{BPS, PLAY_PAUSE_DURATION};
-use crate::raagas::{utils, swars};
+use crate::raagas::composition::Composition;
+use crate::raagas::sound::{AudioDevice, Hertz};
+use crate::raagas::swars;
 use crate::raagas::swarblocks::SwarBlocks;
 use crate::raagas::swars::Swar;
 use crate::raagas::aroha::Aroha;
 use crate::raagas::avroha::Avroha;
+use crate::raagas::tuning::TuningOverride;
+use crate::raagas::tabla::Theka;
+use crate::raagas::taal::TaalSound;
+use crate::raagas::arrangement::Arrangement;
+use rodio::{decoder, Source};
+use std::fs::File;
+use std::io::BufReader;
+use std::time::Duration;
+use crate::raagas::constants::BEAT_MP3;
 
 #[derive(Clone)]
 pub struct Raag {
-    swarmaalika: Swarmaalika,
+    composition: Composition,
     name: String,
     aroha: Aroha,
     avroha: Avroha,
     pakad: Option<SwarBlocks>,
     alankars: Option<SwarBlocks>,
-    beat_src: Option<swars::BeatSrc>,
+    tuning: Option<TuningOverride>,
+    arrangement: Option<Arrangement>,
 }
 
 impl Raag {
@@ -25,8 +35,7 @@ impl Raag {
         avroha: Avroha,
         pakad: Option<SwarBlocks>,
         alankars: Option<SwarBlocks>,
-        swarmaalika: Swarmaalika,
-        beat_src: Option<swars::BeatSrc>,
+        composition: Composition,
     ) -> Raag {
         Raag {
             name,
@@ -34,11 +43,24 @@ impl Raag {
             avroha,
             pakad,
             alankars,
-            swarmaalika,
-            beat_src,
+            composition,
+            tuning: None,
+            arrangement: None,
         }
     }
 
+    /// Returns the raag with its own adjustments to the tuning
+    pub fn with_tuning(mut self, tuning: Option<TuningOverride>) -> Raag {
+        self.tuning = tuning;
+        self
+    }
+
+    /// Returns the raag played as `arrangement` rather than by default
+    pub fn with_arrangement(mut self, arrangement: Option<Arrangement>) -> Raag {
+        self.arrangement = arrangement;
+        self
+    }
+
     pub fn name(&self) -> String {
         self.name.to_string()
     }
@@ -59,37 +81,57 @@ impl Raag {
         &self.alankars
     }
 
-    pub fn swarmaalika(&self) -> &Swarmaalika {
-        &self.swarmaalika
+    pub fn composition(&self) -> &Composition {
+        &self.composition
     }
 
-    pub fn beat_src(&self) -> &Option<swars::BeatSrc> {
-        &self.beat_src
+    /// Returns the sections of the composition set to a taal; `None` for an
+    /// alap
+    pub fn swarmaalika(&self) -> Option<&Swarmaalika> {
+        self.composition.swarmaalika()
     }
 
-    fn play_aroha(&self, dev: &AudioDevice) {
-        println!("\n=> playing aroha  {}", self.aroha.aroha());
-        self.aroha.play(&dev);
+    /// The raag's adjustments to the tuning it is played in
+    pub fn tuning(&self) -> &Option<TuningOverride> {
+        &self.tuning
     }
 
-    fn play_avroha(&self, dev: &AudioDevice) {
-        println!("\n=> playing avroha  {}", self.avroha.avroha());
-        self.avroha.play(&dev);
+    /// The order the raag's parts are played in, if it isn't the default
+    pub fn arrangement(&self) -> Option<&Arrangement> {
+        self.arrangement.as_ref()
+    }
+
+    /// Returns the theka of the composition's taal played at `sample_rate` on
+    /// the tabla tuned to Sa at `sa`, if it is set to a taal; an error if the
+    /// theka has bols the tabla can't play
+    pub fn taal_sound(&self, sa: &Hertz, sample_rate: u32) -> Result<Option<Box<dyn TaalSound>>, String> {
+        let taal = match self.swarmaalika().and_then(|s| s.taal()) {
+            Some(taal) => taal,
+            _ => return Ok(None),
+        };
+
+        Ok(Some(Box::new(Theka::new(taal, sa.freq(), sample_rate)?)))
     }
 
-    fn play_pakad(&self, dev: &AudioDevice) {
-        println!("\n=> playing pakad  {}", self.pakad.as_ref().unwrap());
-        self.pakad.as_ref().unwrap().play(&dev);
+    /// Returns the beat sample played on every matra of the composition's
+    /// taal at `sample_rate`, if it is set to a taal; an error if the sample
+    /// can't be read
+    pub fn beat_sound(&self, sample_rate: u32) -> Result<Option<Box<dyn TaalSound>>, String> {
+        let taal = match self.swarmaalika().and_then(|s| s.taal()) {
+            Some(taal) => taal,
+            _ => return Ok(None),
+        };
+        let beat = play_raw_beats_forever(BEAT_MP3)?.convert_samples::<f32>();
+
+        Ok(Some(Box::new(taal.beats(beat, sample_rate))))
     }
 
-    // fn play_alankars(&self, dev: &AudioDevice, vol: f32) {
-    //     // println!("\n=> playing alankars  {:?}", self.alankars.unwrap());
-    //     self.alankars.as_ref().unwrap().play(&dev, vol);
-    // }
+    /// Returns the swars of the aroha followed by those of the avroha
+    pub fn swars(&self) -> Vec<&Swar> {
+        let mut swars = self.aroha.aroha().to_swars_as_ref();
+        swars.extend(self.avroha.avroha().to_swars_as_ref());
 
-    fn play_swarmaalika(&self, dev: &AudioDevice)  {
-        println!("\n=> playing swarmaalika");
-        self.swarmaalika.play(&dev);
+        swars
     }
 
     /// check if the `swars` are in aroha in all three octaves
@@ -111,22 +153,30 @@ impl Raag {
     }
 
     pub fn play(&self, dev: &AudioDevice) {
-        println!("=> playing raag: {}", self.name());
-
-        self.play_aroha(&dev);
-        utils::delay(PLAY_PAUSE_DURATION * BPS);
-        self.play_avroha(&dev);
-        utils::delay(PLAY_PAUSE_DURATION * BPS);
-        self.play_pakad(&dev);
-        utils::delay(PLAY_PAUSE_DURATION * BPS);
-        self.play_swarmaalika(&dev);
-        utils::delay(PLAY_PAUSE_DURATION * BPS);
+        dev.play(self);
     }
 }
 
+/// Reads the beat sample at `beatp.0`, lasting `beatp.1` seconds if it
+/// doesn't say, to be played over and over
+fn play_raw_beats_forever(beatp: (&str, f32)) -> Result<swars::BeatSrc, String> {
+    let f = File::open(beatp.0).map_err(|e| format!("Unable to open file {}: {}", beatp.0, e))?;
+    let source = decoder::Decoder::new(BufReader::new(f))
+        .map_err(|e| format!("Unable to decode file {}: {}", beatp.0, e))?;
+    // we are having to do this as the total_duration is returned none for
+    // wav, mp3 files in some cases.
+    let t = match source.total_duration() {
+        Some(_t) => _t,
+        _ => Duration::from_secs_f32(beatp.1),
+    };
+
+    Ok(source.take_duration(t).repeat_infinite())
+}
+
 #[cfg(test)]
 mod tests {
     use crate::raagas::raag::load;
+    use crate::raagas::raag::raag::play_raw_beats_forever;
 
     /// test if raag composition can be loaded and contains parts:
     /// aroha, avroha, pakad, alankars, sthayi, antara
@@ -140,8 +190,14 @@ mod tests {
         assert!(raag.avroha().avroha().0.len() > 0);
         assert!(raag.pakad().is_some());
         assert!(raag.alankars().is_some());
-        assert_eq!(raag.swarmaalika().sam(), 1);
-        assert!(!raag.swarmaalika().sthayi.lines.is_empty());
-        assert!(!raag.swarmaalika().antara.lines.is_empty());
+        assert_eq!(raag.swarmaalika().unwrap().sam(), 1);
+        assert!(!raag.swarmaalika().unwrap().sthayi.lines.is_empty());
+        assert!(!raag.swarmaalika().unwrap().antara.lines.is_empty());
     }
-}
\ No newline at end of file
+    /// test a beat sample that can't be read is an error rather than a panic
+    #[test]
+    fn test_beat_sample_missing() {
+        let e = play_raw_beats_forever(("./samples/no_such_beat.mp3", 0.7)).err().unwrap();
+        assert!(e.starts_with("Unable to open file ./samples/no_such_beat.mp3: "));
+    }
+}
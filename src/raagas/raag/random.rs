@@ -3,7 +3,8 @@ use rand;
 use rand::seq::SliceRandom;
 
 use rand::prelude::ThreadRng;
-use crate::raagas::sound::Pitch;
+use crate::raagas::pitch::Pitch;
+use crate::raagas::beats::Beats;
 use crate::raagas::raag::raag::Raag;
 use crate::raagas::{SimpleRandomiser, PureRandomiser, Mutate};
 use crate::raagas::swarblocks::SwarBlocks;
@@ -76,7 +77,7 @@ impl PureRandomiser for Raag {
         }
 
         let mut swars: Vec<Swar> = Vec::new();
-        swars.push(Swar::new(Pitch::new("S".to_string()), 3.0));
+        swars.push(Swar::new(Pitch::new("S".to_string()), Beats::whole(3)));
         // choose swars in aroha and some swars in avroha
         // as the swars are not the same between the two.
 
@@ -84,7 +85,7 @@ impl PureRandomiser for Raag {
         for i in _inds {
             if let Some(sw) = _swars.get(i) {
                 if let Some(p) = sw.pitch.as_ref() {
-                    swars.push(Swar::new(p.clone(), 2.0));
+                    swars.push(Swar::new(p.clone(), Beats::whole(2)));
                 } else {
                     return Err(format!("Pitch can't be empty!"));
                 }
@@ -97,7 +98,7 @@ impl PureRandomiser for Raag {
         }
 
         // end with S
-        swars.push(Swar::new(Pitch::new("S".to_string()), 3.0));
+        swars.push(Swar::new(Pitch::new("S".to_string()), Beats::whole(3)));
 
         Ok(swars)
     }
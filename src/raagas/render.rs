@@ -0,0 +1,861 @@
+use std::path::Path;
+use std::time::Duration;
+
+use rodio::Source;
+
+use crate::raagas::constants::{self, BPM, TANPURA_CYCLE};
+use crate::raagas::laya::{self, Tempo};
+use crate::raagas::taal::TaalSound;
+use crate::raagas::pitch::Pitch;
+use crate::raagas::instrument::Instrument;
+use crate::raagas::envelope::Envelope;
+use crate::raagas::tanpura::Tanpura;
+use crate::raagas::swars::Swar;
+use crate::raagas::swarblock::SwarBlock;
+use crate::raagas::swarblocks::SwarBlocks;
+use crate::raagas::aroha::Aroha;
+use crate::raagas::avroha::Avroha;
+use crate::raagas::swarmaalika::{Line, Sthayi, Antara, Swarmaalika};
+use crate::raagas::composition::{Composition, Form};
+use crate::raagas::beats::Beats;
+use crate::raagas::arrangement::{Arrangement, Cue, Parts, Plan, LINE_TIMES};
+use crate::raagas::raag::raag::Raag;
+use crate::raagas::tuning::{Tuning, TuningOverride};
+use crate::raagas::sound::Hertz;
+use crate::raagas::timeline::{Event, Timeline};
+use crate::raagas::{Melody, Render};
+
+/// Schedules melodies on a timeline, to be mixed into a mono PCM buffer (so
+/// they can be written out as a WAV file without an audio device) or played
+/// through a single sink. Swars are laid out one after the other from a
+/// cursor, with their releases mixed in under what follows.
+pub struct Renderer {
+    sample_rate: u32,
+    vol: f32,
+    instrument: Instrument,
+    drone: Option<Pitch>,
+    envelope: Option<Envelope>,
+    tanpura: Option<Tanpura>,
+    sa: Hertz,
+    tuning: Tuning,
+    events: Vec<Event>,
+    // messages shown as the melody reaches a sample, e.g. its sections
+    marks: Vec<(usize, String)>,
+    samples: Vec<f32>,
+    cursor: usize,
+    // where the last event ends
+    end: usize,
+    // the tempo: beats per minute, changing by `accel` every beat for the
+    // next `ramp` beats
+    bpm: f32,
+    accel: f32,
+    ramp: f32,
+    // where each beat starts, while a taal is played under the melody, and
+    // the beats played since it started
+    onsets: Option<Vec<usize>>,
+    beat: Beats,
+}
+
+impl Renderer {
+    pub fn new(sample_rate: u32, vol: f32) -> Self {
+        Renderer {
+            sample_rate,
+            vol,
+            instrument: Instrument::default(),
+            drone: None,
+            envelope: None,
+            tanpura: None,
+            sa: constants::tonic(),
+            tuning: Tuning::default(),
+            events: Vec::new(),
+            marks: Vec::new(),
+            samples: Vec::new(),
+            cursor: 0,
+            end: 0,
+            bpm: BPM,
+            accel: 0.0,
+            ramp: 0.0,
+            onsets: None,
+            beat: Beats::ZERO,
+        }
+    }
+
+    /// Returns the renderer playing at `bpm` beats per minute
+    pub fn with_bpm(mut self, bpm: f32) -> Self {
+        self.bpm = bpm;
+        self
+    }
+
+    /// Returns the renderer playing swars with `instrument`
+    pub fn with_instrument(mut self, instrument: Instrument) -> Self {
+        self.instrument = instrument;
+        self
+    }
+
+    /// Returns the renderer sounding `drone` under every swar
+    pub fn with_drone(mut self, drone: Option<Pitch>) -> Self {
+        self.drone = drone;
+        self
+    }
+
+    /// Returns the renderer shaping swars with `envelope` rather than the
+    /// instrument's own envelope
+    pub fn with_envelope(mut self, envelope: Option<Envelope>) -> Self {
+        self.envelope = envelope;
+        self
+    }
+
+    /// Returns the renderer accompanying melodies with `tanpura`
+    pub fn with_tanpura(mut self, tanpura: Option<Tanpura>) -> Self {
+        self.tanpura = tanpura;
+        self
+    }
+
+    /// Returns the renderer playing swars with Sa at `sa`
+    pub fn with_sa(mut self, sa: Hertz) -> Self {
+        self.sa = sa;
+        self
+    }
+
+    /// Returns the renderer playing swars in `tuning`
+    pub fn with_tuning(mut self, tuning: Tuning) -> Self {
+        self.tuning = tuning;
+        self
+    }
+
+    pub fn envelope(&self) -> Envelope {
+        self.envelope.unwrap_or_else(|| self.instrument.envelope())
+    }
+
+    /// Returns the frequency of Sa swars are played at
+    pub fn sa(&self) -> &Hertz {
+        &self.sa
+    }
+
+    /// Returns the tuning the next swar is played in
+    pub fn tuning(&self) -> &Tuning {
+        &self.tuning
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Returns the tempo the next swar is played at, in beats per minute
+    pub fn bpm(&self) -> f32 {
+        self.bpm
+    }
+
+    /// Plays on at `tempo`, which changes (if it does) over the next `beats`
+    /// beats
+    pub fn set_tempo(&mut self, tempo: &Tempo, beats: Beats) {
+        self.bpm = tempo.bpm;
+        match tempo.to {
+            Some(to) if beats > Beats::ZERO => {
+                self.accel = (to - tempo.bpm) / beats.as_f32();
+                self.ramp = beats.as_f32();
+            },
+            _ => {
+                self.bpm = tempo.to.unwrap_or(tempo.bpm);
+                self.accel = 0.0;
+                self.ramp = 0.0;
+            },
+        }
+    }
+
+    // seconds from now until `beats` beats on
+    fn secs(&self, beats: f32) -> f32 {
+        let ramped = beats.min(self.ramp);
+        let to = self.bpm + self.accel * ramped;
+        laya::secs(self.bpm, self.accel, ramped) + laya::secs(to, 0.0, beats - ramped)
+    }
+
+    // moves the tempo on by `beats` beats, returning how long they last (in
+    // seconds) and noting where the beats of the taal start within them
+    fn advance(&mut self, beats: Beats) -> f32 {
+        if let Some(mut onsets) = self.onsets.take() {
+            while Beats::from(onsets.len()) < self.beat + beats {
+                let offset = self.secs((Beats::from(onsets.len()) - self.beat).max(Beats::ZERO).as_f32());
+                onsets.push(self.cursor + self.no_samples(offset));
+            }
+            self.onsets = Some(onsets);
+        }
+
+        let secs = self.secs(beats.as_f32());
+        let ramped = beats.as_f32().min(self.ramp);
+        self.bpm += self.accel * ramped;
+        self.ramp -= ramped;
+        if self.ramp <= 0.0 {
+            self.accel = 0.0;
+        }
+        self.beat += beats;
+
+        secs
+    }
+
+    /// Returns the samples rendered so far, mixing in what has been scheduled
+    pub fn samples(&mut self) -> &[f32] {
+        let events = std::mem::take(&mut self.events);
+        let timeline = Timeline::new(events, self.end, self.sample_rate);
+        self.samples.resize(self.end, 0.0);
+        for (s, v) in self.samples.iter_mut().zip(timeline) {
+            *s += v;
+        }
+
+        &self.samples
+    }
+
+    /// Returns the length (in seconds) of what has been rendered so far,
+    /// including the release of the last swar
+    pub fn duration(&self) -> f32 {
+        self.end as f32 / self.sample_rate as f32
+    }
+
+    /// Shows `msg` when the melody reaches the cursor
+    pub fn mark(&mut self, msg: String) {
+        self.marks.push((self.cursor, msg));
+    }
+
+    /// Returns the messages to show, with the sample they are shown at
+    pub fn marks(&self) -> &[(usize, String)] {
+        &self.marks
+    }
+
+    /// Returns the timeline of all that has been scheduled, to be played
+    pub fn into_timeline(mut self) -> Timeline {
+        if !self.samples.is_empty() {
+            self.schedule(0, None, 1.0, self.samples.clone().into_iter());
+        }
+
+        Timeline::new(self.events, self.end, self.sample_rate)
+    }
+
+    // schedules `src` to play from sample `at` (until sample `until`, if given)
+    fn schedule<S>(&mut self, at: usize, until: Option<usize>, vol: f32, src: S)
+    where
+        S: Iterator<Item = f32> + Send + 'static,
+    {
+        self.events.push(Event { at, until, vol, src: Box::new(src) });
+    }
+
+    /// Returns the number of samples up to the end of the last swar or pause,
+    /// where the next one starts
+    pub fn position(&self) -> usize {
+        self.cursor
+    }
+
+    fn no_samples(&self, t: f32) -> usize {
+        (t * self.sample_rate as f32).round() as usize
+    }
+
+    /// Appends `t` seconds of silence
+    pub fn delay(&mut self, t: f32) {
+        self.cursor += self.no_samples(t);
+        self.end = self.end.max(self.cursor);
+    }
+
+    /// Appends a pause of `beats` beats at the current tempo
+    pub fn rest(&mut self, beats: Beats) {
+        let t = self.advance(beats);
+        self.delay(t);
+    }
+
+    /// Appends a swar for its beat count, played with the renderer's instrument
+    /// (mixed with its drone, if any) and shaped by its envelope; a swar
+    /// without a pitch is a pause.
+    pub fn swar(&mut self, sw: &Swar) {
+        let t = self.advance(sw.beat_cnt);
+        let src = sw.source(
+            Duration::from_secs_f32(t),
+            self.instrument,
+            self.drone.as_ref(),
+            self.envelope(),
+            |p| p.hertz(&self.sa, &self.tuning),
+            self.sample_rate
+        );
+        if let Some(src) = src {
+            let len = src.total_duration().map_or(0, |d| self.no_samples(d.as_secs_f32()));
+            let until = self.cursor + len;
+            self.schedule(self.cursor, Some(until), self.vol, src);
+            self.end = self.end.max(until);
+        }
+        self.delay(t);
+    }
+
+    /// Renders the melody of `swars` in `render`, with the tanpura (if any)
+    /// starting a cycle before it and sounding until it is over
+    pub fn accompany<F: FnOnce(&mut Renderer)>(&mut self, swars: &[&Swar], render: F) {
+        let tanpura = match self.tanpura {
+            Some(tanpura) => tanpura,
+            _ => return render(self),
+        };
+        let start = self.cursor;
+        self.delay(TANPURA_CYCLE);
+        render(self);
+
+        let src = tanpura.source(swars, &self.sa, &self.tuning, self.sample_rate);
+        self.schedule(start, Some(self.end), tanpura.vol(), src);
+    }
+
+    /// Renders `render` in the renderer's tuning as adjusted by `o` (if
+    /// given), e.g. with a raag's ati-komal re, then goes back to its own
+    pub fn retune<F: FnOnce(&mut Renderer)>(&mut self, o: Option<&TuningOverride>, render: F) {
+        let tuning = match o {
+            Some(o) => self.tuning.overridden_by(o),
+            _ => return render(self),
+        };
+        let own = std::mem::replace(&mut self.tuning, tuning);
+        render(self);
+        self.tuning = own;
+    }
+
+    /// Renders `render` with `taal` sounding under it from matra `from`, a
+    /// matra to every beat however the tempo changes
+    pub fn play_over<T, F>(&mut self, taal: &T, from: usize, render: F)
+    where
+        T: TaalSound + ?Sized,
+        F: FnOnce(&mut Renderer),
+    {
+        let outer = (self.onsets.replace(Vec::new()), self.beat);
+        self.beat = Beats::ZERO;
+        render(self);
+        let onsets = std::mem::replace(&mut self.onsets, outer.0).unwrap_or_default();
+        self.beat += outer.1;
+
+        let beat_len = self.no_samples(self.secs(1.0));
+        for (k, at) in onsets.iter().enumerate() {
+            let len = onsets.get(k + 1).map_or(beat_len, |next| next - at);
+            let src = taal.matra(from + k, len);
+            self.schedule(*at, Some(self.end), self.vol, src);
+        }
+    }
+
+    /// Writes the rendered samples as a 16-bit mono WAV file
+    pub fn write_wav<P: AsRef<Path>>(&mut self, path: P) -> Result<(), hound::Error> {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: self.sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(path, spec)?;
+        for s in self.samples() {
+            let sample = s.clamp(-1.0, 1.0) * i16::MAX as f32;
+            writer.write_sample(sample as i16)?;
+        }
+
+        writer.finalize()
+    }
+}
+
+impl Render for Swar {
+    fn render(&self, r: &mut Renderer) {
+        r.swar(self);
+    }
+}
+
+impl Render for SwarBlock {
+    fn render(&self, r: &mut Renderer) {
+        for sw_bt in &self.0 {
+            for sw in &sw_bt.swars {
+                sw.render(r);
+            }
+        }
+    }
+}
+
+impl Render for SwarBlocks {
+    fn render(&self, r: &mut Renderer) {
+        for blk in &self.0 {
+            blk.render(r);
+        }
+    }
+}
+
+impl Render for Aroha {
+    fn render(&self, r: &mut Renderer) {
+        self.aroha().render(r);
+    }
+}
+
+impl Render for Avroha {
+    fn render(&self, r: &mut Renderer) {
+        self.avroha().render(r);
+    }
+}
+
+impl Line {
+    /// Renders the line `no_times` times, showing its notation (with its
+    /// bols under it) first; `i` is its place in its section
+    pub fn render_line(&self, i: usize, no_times: usize, r: &mut Renderer) {
+        r.mark(format!("line: {}", self.name(i)));
+        r.mark(self.notation());
+        for _ in 0..no_times {
+            self.blks.render(r);
+        }
+    }
+}
+
+impl Sthayi {
+    /// Renders the `i`th line (from 0) of the sthayi `no_times` times
+    pub fn render_line(&self, i: usize, no_times: usize, r: &mut Renderer) {
+        if let Some(line) = self.lines.get(i) {
+            line.render_line(i, no_times, r);
+        }
+    }
+
+    /// Returns the number of beats the sthayi is played over
+    pub fn beats(&self) -> Beats {
+        line_beats(&self.lines, LINE_TIMES)
+    }
+}
+
+impl Render for Sthayi {
+    /// Renders each line of the sthayi in turn, `LINE_TIMES` times
+    fn render(&self, r: &mut Renderer) {
+        for i in 0..self.lines.len() {
+            self.render_line(i, LINE_TIMES, r);
+        }
+    }
+}
+
+impl Antara {
+    /// Renders the `i`th line (from 0) of the antara `no_times` times
+    pub fn render_line(&self, i: usize, no_times: usize, r: &mut Renderer) {
+        if let Some(line) = self.lines.get(i) {
+            line.render_line(i, no_times, r);
+        }
+    }
+
+    /// Returns the number of beats the antara is played over
+    pub fn beats(&self) -> Beats {
+        line_beats(&self.lines, LINE_TIMES)
+    }
+}
+
+impl Render for Antara {
+    /// Renders each line of the antara in turn, `LINE_TIMES` times
+    fn render(&self, r: &mut Renderer) {
+        for i in 0..self.lines.len() {
+            self.render_line(i, LINE_TIMES, r);
+        }
+    }
+}
+
+// the beats of `lines` each played `no_times` times
+fn line_beats(lines: &[Line], no_times: usize) -> Beats {
+    lines.iter().map(|line| line.blks.beats() * no_times as i64).sum()
+}
+
+impl Render for Cue<'_> {
+    fn render(&self, r: &mut Renderer) {
+        match self {
+            Cue::Mark(msg) => r.mark(msg.clone()),
+            Cue::Tempo(tempo, beats) => r.set_tempo(tempo, *beats),
+            Cue::Play(blks) => blks.render(r),
+            Cue::Pause(beats) => r.rest(*beats),
+        }
+    }
+}
+
+impl Render for [Cue<'_>] {
+    fn render(&self, r: &mut Renderer) {
+        for cue in self {
+            cue.render(r);
+        }
+    }
+}
+
+impl Render for Plan<'_> {
+    fn render(&self, r: &mut Renderer) {
+        self.cues.render(r);
+    }
+}
+
+impl Render for Swarmaalika {
+    /// Renders the mukra, the sthayi and the antara, each followed by the
+    /// first line of the sthayi, and then the tihayi; each section at its own
+    /// tempo, if it has one
+    fn render(&self, r: &mut Renderer) {
+        Arrangement::composition(Form::Swarmaalika).plan(&Parts::from(self)).render(r);
+    }
+}
+
+impl Render for Composition {
+    /// Renders the sections of the composition in the order of its form,
+    /// each at its own tempo, if it has one
+    fn render(&self, r: &mut Renderer) {
+        Arrangement::composition(self.form()).plan(&Parts::from(self)).render(r);
+    }
+}
+
+impl Render for Raag {
+    /// Renders the raag as it is arranged, by default the aroha, avroha,
+    /// pakad and composition with a pause after each; the composition over
+    /// its taal
+    fn render(&self, r: &mut Renderer) {
+        r.mark(format!("=> playing raag: {}", self.name()));
+        r.retune(self.tuning().as_ref(), |r| {
+            let form = self.composition().form();
+            let default = Arrangement::raag(form);
+            let plan = self.arrangement().unwrap_or(&default).plan(&Parts::from(self));
+            r.accompany(&self.swars(), |r| {
+                let mut at = 0;
+                let mut sound = None;
+                for (in_taal, from) in &plan.swarmaalika {
+                    plan.cues[at..in_taal.start].render(r);
+                    at = in_taal.end;
+                    let cues = &plan.cues[in_taal.clone()];
+                    let swarmaalika = match self.swarmaalika() {
+                        Some(swarmaalika) if !in_taal.is_empty() => swarmaalika,
+                        _ => {
+                            cues.render(r);
+                            continue;
+                        },
+                    };
+                    let first = sound.is_none();
+                    let sound = sound.get_or_insert_with(|| {
+                        r.mark(format!("\n=> playing {}", form));
+                        // the beat sample stands in for a theka the tabla can't play
+                        match self.taal_sound(r.sa(), r.sample_rate()) {
+                            Ok(sound) => sound,
+                            Err(e) => {
+                                r.mark(format!("   {}, playing the beat instead", e));
+                                self.beat_sound(r.sample_rate()).unwrap_or_else(|e| {
+                                    r.mark(format!("   {}", e));
+                                    None
+                                })
+                            },
+                        }
+                    });
+                    match (swarmaalika.taal(), from, sound) {
+                        (Some(taal), Some(from), Some(sound)) => {
+                            if first {
+                                r.mark(format!("   in {}: {}", taal.name(), taal));
+                                if let Some(notation) = swarmaalika.notation() {
+                                    r.mark(notation);
+                                }
+                            }
+                            r.play_over(sound.as_ref(), *from, |r| cues.render(r))
+                        },
+                        _ => cues.render(r),
+                    }
+                }
+                plan.cues[at..].render(r);
+            });
+        });
+    }
+}
+
+impl Render for Melody {
+    fn render(&self, r: &mut Renderer) {
+        match self {
+            Melody::SwarBlocks(blks) => {
+                r.accompany(&blks.to_swars_as_ref(), |r| blks.render(r))
+            },
+            Melody::SwarBlock(blk) => {
+                r.accompany(&blk.to_swars_as_ref(), |r| blk.render(r))
+            },
+            Melody::Raag(raag) => raag.render(r),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::raagas::render::Renderer;
+    use crate::raagas::swarblock::SwarBlock;
+    use crate::raagas::raag::load;
+    use crate::raagas::constants::{self, BPS, PLAY_PAUSE_DURATION, TANPURA_CYCLE};
+    use crate::raagas::Render;
+    use crate::raagas::instrument::Instrument;
+    use crate::raagas::envelope::Envelope;
+    use crate::raagas::pitch::{Degree, Pitch, Variant};
+    use crate::raagas::tanpura::Tanpura;
+    use crate::raagas::taal::TaalSound;
+    use crate::raagas::laya::{self, Laya, Tempo, TempoMap};
+    use crate::raagas::Melody;
+    use crate::raagas::beats::Beats;
+    use crate::raagas::arrangement::{Arrangement, Part, Step};
+    use crate::raagas::swarblocks::SwarBlocks;
+    use crate::raagas::swarmaalika::Line;
+    use crate::raagas::composition::{Alap, Composition};
+    use crate::raagas::tuning::{Tuning, TuningOverride, TuningSystem, shruti_index};
+    use crate::raagas::sound::Hertz;
+    use std::collections::BTreeMap;
+
+    /// test the rendered length matches the beat count of the swars
+    #[test]
+    fn test_render_swarblock_length() {
+        let blk = SwarBlock::from("S - R:G");
+        let mut r = Renderer::new(8000, 0.5);
+        blk.render(&mut r);
+        let expected = (3.0 * BPS * 8000.0).round() as usize;
+        assert_eq!(r.position(), expected);
+        // the last swar rings on for its release
+        let release = (r.envelope().release * 8000.0).round() as usize;
+        assert_eq!(r.samples().len(), expected + release);
+    }
+
+    /// test a blank swar is rendered as silence
+    #[test]
+    fn test_render_blank_swar_is_silent() {
+        let blk = SwarBlock::from(":S");
+        let mut r = Renderer::new(8000, 0.5);
+        blk.render(&mut r);
+        let half = (0.5 * BPS * 8000.0).round() as usize;
+        assert!(r.samples()[..half].iter().all(|s| *s == 0.0));
+        assert!(r.samples()[half..].iter().any(|s| *s != 0.0));
+    }
+
+    /// test a drone is only mixed under swars when one is set
+    #[test]
+    fn test_render_with_drone() {
+        let blk = SwarBlock::from("S :S");
+        let flat = Some(Envelope::new(0.0, 0.0, 1.0, 0.0));
+        let mut plain = Renderer::new(8000, 0.5)
+            .with_instrument(Instrument::Sine)
+            .with_envelope(flat);
+        blk.render(&mut plain);
+        let mut droned = Renderer::new(8000, 0.5)
+            .with_instrument(Instrument::Sine)
+            .with_envelope(flat)
+            .with_drone(Some(Pitch::new("M".to_string())));
+        blk.render(&mut droned);
+
+        assert_eq!(plain.samples().len(), droned.samples().len());
+        assert_ne!(plain.samples(), droned.samples());
+        // the drone is not played during a pause
+        let pause = (1.0 * BPS * 8000.0).round() as usize..(1.5 * BPS * 8000.0).round() as usize;
+        assert!(droned.samples()[pause].iter().all(|s| *s == 0.0));
+    }
+
+    /// test swars are played with Sa at the renderer's tonic
+    #[test]
+    fn test_render_with_sa() {
+        let blk = SwarBlock::from("S P");
+        let mut default = Renderer::new(8000, 0.5);
+        blk.render(&mut default);
+        let d = "D".parse::<Hertz>().unwrap();
+        let mut at_d = Renderer::new(8000, 0.5).with_sa(d.clone());
+        blk.render(&mut at_d);
+        assert_eq!(at_d.sa(), &d);
+        assert_eq!(default.sa(), &constants::tonic());
+        assert_ne!(default.samples(), at_d.samples());
+    }
+
+    /// test swars are played in the renderer's tuning, adjusted only while
+    /// retuned
+    #[test]
+    fn test_render_with_tuning() {
+        let blk = SwarBlock::from("S d");
+        let ji = Tuning::new(TuningSystem::JustIntonation);
+        let mut equal = Renderer::new(8000, 0.5);
+        blk.render(&mut equal);
+        let mut just = Renderer::new(8000, 0.5).with_tuning(ji.clone());
+        blk.render(&mut just);
+        assert_ne!(equal.samples(), just.samples());
+
+        let mut shrutis = BTreeMap::new();
+        shrutis.insert((Degree::Dha, Variant::Komal), shruti_index("ati-komal dha").unwrap());
+        let o = TuningOverride { system: None, shrutis };
+        let mut r = Renderer::new(8000, 0.5).with_tuning(ji.clone());
+        r.retune(Some(&o), |r| assert_eq!(r.tuning(), &ji.overridden_by(&o)));
+        assert_eq!(r.tuning(), &ji);
+    }
+
+    /// test a raag is rendered with the pauses between its sections
+    #[test]
+    fn test_render_raag_length() {
+        let raag = load::load_yaml("durga", "durga").unwrap();
+        let mut r = Renderer::new(8000, 0.5);
+        raag.render(&mut r);
+
+        let mut sections = Renderer::new(8000, 0.5);
+        raag.aroha().render(&mut sections);
+        raag.avroha().render(&mut sections);
+        raag.pakad().as_ref().unwrap().render(&mut sections);
+        raag.swarmaalika().unwrap().render(&mut sections);
+        let pauses = (PLAY_PAUSE_DURATION.as_f32() * BPS * 8000.0).round() as usize * 4;
+        assert_eq!(r.position(), sections.position() + pauses);
+    }
+
+    /// test a raag is rendered as it is arranged, the tempo of a part ramping
+    /// over its swars only
+    #[test]
+    fn test_render_raag_arranged() {
+        let arrangement = Arrangement(vec![
+            Step::new(Part::Pakad).with_tempo(Tempo::ramp(60.0, 120.0)),
+            Step::new(Part::Pause(Beats::ONE)),
+            Step::new(Part::SthayiLine("lineB".to_string())).with_times(2),
+        ]);
+        let raag = load::load_yaml("durga", "durga").unwrap().with_arrangement(Some(arrangement));
+        let mut r = Renderer::new(8000, 0.5);
+        raag.render(&mut r);
+
+        let pakad = raag.pakad().as_ref().unwrap();
+        let line = &raag.swarmaalika().unwrap().sthayi.line("lineB").unwrap().blks;
+        let mut parts = Renderer::new(8000, 0.5);
+        parts.set_tempo(&Tempo::ramp(60.0, 120.0), pakad.beats());
+        pakad.render(&mut parts);
+        parts.rest(Beats::ONE);
+        line.render(&mut parts);
+        line.render(&mut parts);
+        assert_eq!(r.position(), parts.position());
+        assert_eq!(r.bpm(), 120.0);
+    }
+
+    /// test an alap is rendered section by section, each at its laya
+    #[test]
+    fn test_render_alap() {
+        let line = |s: &str| Line::new(SwarBlocks(vec![SwarBlock::from(s)]));
+        let alap = Alap::new(vec![line("S R G")], vec![line("S R G")], vec![line("S R G")]);
+        let composition = Composition::Alap(alap).with_tempos(TempoMap::new());
+        let mut r = Renderer::new(8000, 0.5);
+        composition.render(&mut r);
+        let headings: Vec<&str> = r.marks().iter()
+            .map(|(_, m)| m.as_str())
+            .filter(|m| m.starts_with("\n=>"))
+            .collect();
+        assert_eq!(headings, ["\n=> playing alap", "\n=> playing jor", "\n=> playing jhala"]);
+        assert_eq!(r.bpm(), Laya::Drut.bpm());
+
+        // each section twice as fast as the one before
+        let mut drut = Renderer::new(8000, 0.5).with_bpm(Laya::Drut.bpm());
+        for _ in 0..(4 + 2 + 1) {
+            line("S R G").blks.render(&mut drut);
+        }
+        assert_eq!(r.position(), drut.position());
+    }
+
+    /// test the taal is mixed under the swarmaalika only
+    #[test]
+    fn test_render_raag_with_taal() {
+        let raag = load::load_yaml("durga", "durga").unwrap();
+        assert!(raag.taal_sound(&constants::tonic(), 8000).unwrap().is_some());
+        assert!(raag.beat_sound(8000).unwrap().is_some());
+        let mut r = Renderer::new(8000, 0.5);
+        raag.render(&mut r);
+        assert!(r.marks().iter().all(|(_, m)| !m.contains("playing the beat instead")));
+
+        let pause = PLAY_PAUSE_DURATION.as_f32() * BPS;
+        let mut sections = Renderer::new(8000, 0.5);
+        raag.aroha().render(&mut sections);
+        sections.delay(pause);
+        raag.avroha().render(&mut sections);
+        sections.delay(pause);
+        raag.pakad().as_ref().unwrap().render(&mut sections);
+        sections.delay(pause);
+        let start = sections.position();
+        raag.swarmaalika().unwrap().render(&mut sections);
+        sections.delay(pause);
+
+        assert_eq!(r.position(), sections.position());
+        assert_eq!(r.samples()[..start], sections.samples()[..start]);
+        assert_ne!(r.samples()[start..], sections.samples()[start..]);
+    }
+
+    /// test swars fade in, and a long swar fades out after its beats instead of
+    /// being cut off
+    #[test]
+    fn test_render_with_envelope() {
+        let blk = SwarBlock::from("S - - -");
+        let mut r = Renderer::new(8000, 0.5).with_instrument(Instrument::Sine);
+        blk.render(&mut r);
+        let end = r.position();
+        let samples = r.samples();
+        assert_eq!(samples[0], 0.0);
+        assert!(samples[end..].iter().any(|s| s.abs() > 0.1));
+        assert!(samples[samples.len() - 10..].iter().all(|s| s.abs() < 0.01));
+    }
+
+    /// test the tanpura starts a cycle before the melody and sounds until its end
+    #[test]
+    fn test_render_with_tanpura() {
+        let melody = Melody::SwarBlock(SwarBlock::from("S R G"));
+        let mut plain = Renderer::new(8000, 0.5);
+        melody.render(&mut plain);
+        let mut r = Renderer::new(8000, 0.5).with_tanpura(Some(Tanpura::default()));
+        melody.render(&mut r);
+
+        let lead = (TANPURA_CYCLE * 8000.0).round() as usize;
+        assert_eq!(r.position(), lead + plain.position());
+        assert_eq!(r.samples().len(), lead + plain.samples().len());
+        assert!(r.samples()[..lead].iter().any(|s| *s != 0.0));
+        let tail = r.samples().len() - 10;
+        assert!(r.samples()[tail..].iter().any(|s| *s != 0.0));
+    }
+
+    /// test swars last for their beats at the tempo set
+    #[test]
+    fn test_render_with_bpm() {
+        let blk = SwarBlock::from("S R G:M");
+        let mut r = Renderer::new(8000, 0.5).with_bpm(120.0);
+        blk.render(&mut r);
+        assert_eq!(r.position(), 3 * 4000);
+        assert_eq!(r.bpm(), 120.0);
+    }
+
+    /// test the taal keeps time with a melody speeding up, a matra to a beat
+    #[test]
+    fn test_render_tempo_ramp() {
+        struct Clicks;
+        impl TaalSound for Clicks {
+            fn matra(&self, _: usize, len: usize) -> Box<dyn Iterator<Item = f32> + Send> {
+                Box::new(std::iter::once(1.0).chain(std::iter::repeat_n(0.0, len - 1)))
+            }
+        }
+
+        let mut r = Renderer::new(8000, 0.5).with_bpm(60.0);
+        r.play_over(&Clicks, 1, |r| {
+            r.set_tempo(&Tempo::ramp(60.0, 120.0), Beats::whole(4));
+            r.rest(Beats::whole(4));
+        });
+        assert_eq!(r.bpm(), 120.0);
+        let secs = laya::secs(60.0, 15.0, 4.0);
+        assert_eq!(r.position(), (secs * 8000.0).round() as usize);
+
+        let clicks: Vec<usize> = r.samples().iter()
+            .enumerate()
+            .filter(|(_, s)| **s != 0.0)
+            .map(|(i, _)| i)
+            .collect();
+        let second = (laya::secs(60.0, 15.0, 1.0) * 8000.0).round() as usize;
+        assert_eq!(clicks[..2], [0, second]);
+        assert_eq!(clicks.len(), 4);
+        // each beat is shorter than the one before
+        assert!(clicks.windows(3).all(|w| w[2] - w[1] < w[1] - w[0]));
+    }
+
+    /// test each section of a swarmaalika is played at its own tempo
+    #[test]
+    fn test_render_swarmaalika_tempos() {
+        let raag = load::load_yaml("yaman", "yaman").unwrap();
+        let swarmaalika = raag.swarmaalika().unwrap();
+        assert_eq!(swarmaalika.tempo("tihayi"), Some(&Tempo::from(Laya::Drut)));
+        let mut r = Renderer::new(8000, 0.5);
+        swarmaalika.render(&mut r);
+        assert_eq!(r.bpm(), Laya::Drut.bpm());
+
+        let steady = swarmaalika.clone().with_tempos(TempoMap::new());
+        let mut plain = Renderer::new(8000, 0.5).with_bpm(80.0);
+        steady.render(&mut plain);
+        // sped up from 80 throughout, so it is over sooner
+        assert!(r.position() < plain.position());
+    }
+
+    /// test the rendered samples can be written to and read back from a WAV file
+    #[test]
+    fn test_write_wav() {
+        let blk = SwarBlock::from("S R G");
+        let mut r = Renderer::new(8000, 0.5);
+        blk.render(&mut r);
+        let fp = std::env::temp_dir().join("rustymusic_test_write_wav.wav");
+        r.write_wav(&fp).unwrap();
+
+        let reader = hound::WavReader::open(&fp).unwrap();
+        assert_eq!(reader.spec().sample_rate, 8000);
+        assert_eq!(reader.len() as usize, r.samples().len());
+        let _ = std::fs::remove_file(&fp);
+    }
+}
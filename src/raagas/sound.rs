@@ -1,22 +1,162 @@
-use rodio::{source::SineWave, OutputStreamHandle};
-use std::fmt::Formatter;
-use std::fmt;
+use rodio::{OutputStreamHandle, Sink};
+use std::str::FromStr;
 
 use crate::raagas::constants;
+use crate::raagas::pitch::Pitch;
+use crate::raagas::instrument::Instrument;
+use crate::raagas::envelope::Envelope;
+use crate::raagas::tanpura::Tanpura;
+use crate::raagas::tuning::Tuning;
+use crate::raagas::render::Renderer;
+use crate::raagas::Render;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
 
 pub struct AudioDevice {
     pub(crate) out_stream_handle: OutputStreamHandle,
     pub(crate) vol: f32,
+    pub(crate) instrument: Instrument,
+    pub(crate) drone: Option<Pitch>,
+    pub(crate) envelope: Option<Envelope>,
+    pub(crate) tanpura: Option<Tanpura>,
+    pub(crate) bpm: f32,
+    pub(crate) sa: Hertz,
+    pub(crate) tuning: Tuning,
 }
 
 impl AudioDevice {
     pub fn new(out_stream_handle: OutputStreamHandle, vol: f32) -> AudioDevice {
-        AudioDevice { out_stream_handle, vol }
+        AudioDevice {
+            out_stream_handle,
+            vol,
+            instrument: Instrument::default(),
+            drone: None,
+            envelope: None,
+            tanpura: None,
+            bpm: constants::BPM,
+            sa: constants::tonic(),
+            tuning: Tuning::default(),
+        }
+    }
+
+    /// Returns the device playing swars with `instrument`
+    pub fn with_instrument(mut self, instrument: Instrument) -> AudioDevice {
+        self.instrument = instrument;
+        self
+    }
+
+    /// Returns the device sounding `drone` under every swar
+    pub fn with_drone(mut self, drone: Option<Pitch>) -> AudioDevice {
+        self.drone = drone;
+        self
+    }
+
+    /// Returns the device shaping swars with `envelope` rather than the
+    /// instrument's own envelope
+    pub fn with_envelope(mut self, envelope: Option<Envelope>) -> AudioDevice {
+        self.envelope = envelope;
+        self
+    }
+
+    /// Returns the device accompanying melodies with `tanpura`
+    pub fn with_tanpura(mut self, tanpura: Option<Tanpura>) -> AudioDevice {
+        self.tanpura = tanpura;
+        self
+    }
+
+    /// Returns the device playing at `bpm` beats per minute
+    pub fn with_bpm(mut self, bpm: f32) -> AudioDevice {
+        self.bpm = bpm;
+        self
+    }
+
+    /// Returns the device playing swars with Sa at `sa`
+    pub fn with_sa(mut self, sa: Hertz) -> AudioDevice {
+        self.sa = sa;
+        self
+    }
+
+    /// Returns the device playing swars in `tuning`
+    pub fn with_tuning(mut self, tuning: Tuning) -> AudioDevice {
+        self.tuning = tuning;
+        self
     }
 
     pub fn vol(&self) -> f32 {
         self.vol
     }
+
+    pub fn bpm(&self) -> f32 {
+        self.bpm
+    }
+
+    pub fn instrument(&self) -> Instrument {
+        self.instrument
+    }
+
+    pub fn envelope(&self) -> Envelope {
+        self.envelope.unwrap_or_else(|| self.instrument.envelope())
+    }
+
+    pub fn drone(&self) -> Option<&Pitch> {
+        self.drone.as_ref()
+    }
+
+    pub fn tanpura(&self) -> Option<&Tanpura> {
+        self.tanpura.as_ref()
+    }
+
+    pub fn sa(&self) -> &Hertz {
+        &self.sa
+    }
+
+    pub fn tuning(&self) -> &Tuning {
+        &self.tuning
+    }
+
+    /// Returns a renderer set up like the device, at `SAMPLE_RATE`
+    pub fn renderer(&self) -> Renderer {
+        Renderer::new(constants::SAMPLE_RATE, self.vol)
+            .with_instrument(self.instrument)
+            .with_drone(self.drone.clone())
+            .with_envelope(self.envelope)
+            .with_tanpura(self.tanpura)
+            .with_bpm(self.bpm)
+            .with_sa(self.sa.clone())
+            .with_tuning(self.tuning.clone())
+    }
+
+    /// Plays `melody` through a single sink, with every swar, pause, drone
+    /// and taal scheduled at its exact sample, until it is over
+    pub fn play<R: Render + ?Sized>(&self, melody: &R) {
+        self.play_rendered(|r| melody.render(r));
+    }
+
+    /// Plays what `render` renders, showing its marks as they are reached
+    pub fn play_rendered<F: FnOnce(&mut Renderer)>(&self, render: F) {
+        let mut r = self.renderer();
+        render(&mut r);
+        let marks = r.marks().to_vec();
+        let sample_rate = r.sample_rate() as f64;
+
+        let sink = match Sink::try_new(&self.out_stream_handle) {
+            Ok(sink) => sink,
+            Err(e) => {
+                println!("Error playing: {}", e);
+                return;
+            }
+        };
+        sink.append(r.into_timeline());
+        let start = Instant::now();
+        for (at, msg) in marks {
+            let t = Duration::from_secs_f64(at as f64 / sample_rate);
+            if let Some(wait) = t.checked_sub(start.elapsed()) {
+                sleep(wait);
+            }
+            println!("{}", msg);
+        }
+        sink.sleep_until_end();
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -37,63 +177,68 @@ impl Hertz {
     pub fn tone(&self) -> &str {
         self.tone.as_ref()
     }
-}
 
-impl From<Hertz> for f64 {
-    fn from(h: Hertz) -> Self {
-        h.freq
-    }
-}
+    /// Returns a Hertz named after the western tone nearest to `freq`
+    pub fn from_freq(freq: f64) -> Self {
+        let mut hz = Hertz::new(freq, String::new());
+        hz.tone = constants::TONES[hz.tone_index() as usize].to_string();
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct Pitch(String);
-
-impl Pitch {
-    pub fn new(name: String) -> Self {
-        Self(name)
+        hz
     }
 
-    pub fn hertz(&self) -> Option<Hertz> {
-        if let Some(hz) = constants::SWARS.get(&*self.0) {
-            return Some(hz.to_owned());
-        }
-
-        None
+    /// Returns the index (in `constants::TONES`) of the tone nearest to this frequency
+    pub(crate) fn tone_index(&self) -> i32 {
+        // A4 (440Hz) is the 9th tone
+        let semitones = (12.0 * (self.freq / 440.0).log2()).round() as i32;
+        (semitones + 9).rem_euclid(12)
     }
+}
 
-    pub fn from_swar(s: &str) -> SineWave {
-        return match constants::SWARS.get(s) {
-            Some(hz) => {
-                SineWave::new(hz.freq as u32)
-            },
-            _ => {
-                SineWave::new(Pitch::default().hertz().unwrap().freq as u32)
+impl FromStr for Hertz {
+    type Err = String;
+
+    /// Parses a tonic, given either as a western tone with an optional octave
+    /// (`C#`, `Db`, `B3`; octave 4 by default) or as a frequency (`261.63`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Ok(freq) = s.parse::<f64>() {
+            if !freq.is_finite() || freq <= 0.0 {
+                return Err(format!("Frequency {} must be positive and finite", s));
             }
+            return Ok(Hertz::from_freq(freq));
         }
-    }
 
-    pub fn to_sinewave(&self) -> Option<SineWave> {
-        if let Some(hz) = self.hertz() {
-            return Some(SineWave::new(hz.freq as u32));
+        let octave_at = s.find(|c: char| c.is_ascii_digit()).unwrap_or(s.len());
+        let (tone, octave) = s.split_at(octave_at);
+        let octave = match octave {
+            "" => 4,
+            _ => octave.parse::<i32>().map_err(|_| format!("Invalid octave in {}", s))?,
+        };
+        let mut chars = tone.chars();
+        let natural = chars.next()
+            .map(|c| c.to_ascii_uppercase().to_string())
+            .unwrap_or_default();
+        let mut index = constants::TONES.iter()
+            .position(|t| *t == natural)
+            .ok_or(format!("Invalid tone {}", s))? as i32;
+        match chars.as_str() {
+            "" => {},
+            "#" => index += 1,
+            "b" => index -= 1,
+            _ => return Err(format!("Invalid tone {}", s)),
         }
-        None
-    }
-}
 
-impl Default for Pitch {
-    fn default() -> Self {
-        Self("S".to_string())
-    }
-}
+        // semitones away from A4
+        let n = (octave - 4) * 12 + index - 9;
+        let freq = 440.0 * 2f64.powf(n as f64 / 12.0);
+        let tone = constants::TONES[index.rem_euclid(12) as usize];
 
-impl From<Pitch> for SineWave {
-    fn from(p: Pitch) -> Self {
-        return SineWave::new(p.hertz().unwrap().freq as u32);
+        Ok(Hertz::new(freq, tone.to_string()))
     }
 }
 
-impl fmt::Display for Pitch {
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        write!(f, "{}", self.0)
+impl From<Hertz> for f64 {
+    fn from(h: Hertz) -> Self {
+        h.freq
     }
-}
\ No newline at end of file
+}
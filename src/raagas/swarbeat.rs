@@ -5,49 +5,80 @@ use rand;
 use rand::seq::SliceRandom;
 
 use crate::raagas::constants::KAN_SWAR_BEAT_COUNT;
+use crate::raagas::beats::Beats;
 
 // maps to 1 beat in a rhythm cycle
 #[derive(Debug, Clone)]
 pub struct SwarBeat {
     // possible combinations:
-    // S, S:S (or :S or S:), S:S:S:S
+    // S, S:S (or :S or S:), S:R:G, S:S:S:S and so on, P/M
     pub swars: Vec<Swar>,
+    // the number of equal parts the beat is split into, and how many of them
+    // the swar before the beat is held over (e.g. 3 and 1 for `-:R:G`)
+    division: usize,
+    offset: usize,
 }
 
 impl SwarBeat {
 
-    /// Returns a new `SwarBeat` based on `Swars`.
+    /// Returns a new `SwarBeat` based on `Swars`, split into the fewest equal
+    /// parts its swars each take up a whole number of.
     pub fn new(swars: Vec<Swar>) -> Self {
+        let division = Beats::division(swars.iter().map(|sw| sw.beat_cnt));
+        // the last swar may be held over into the beats after
+        let offset = match swars.last() {
+            Some(last) if last.beat_cnt < Beats::ONE => {
+                let total: Beats = swars.iter().map(|sw| sw.beat_cnt).sum();
+                (Beats::ONE - total).parts(division).unwrap_or(0)
+            },
+            _ => 0,
+        };
+
+        SwarBeat::divided(swars, division, offset)
+    }
+
+    /// Returns a new `SwarBeat` split into `division` equal parts, with the
+    /// swar before it held over for the first `offset` of them
+    pub fn divided(swars: Vec<Swar>, division: usize, offset: usize) -> Self {
         SwarBeat {
-            swars
+            swars,
+            division,
+            offset,
         }
     }
 
+    /// Returns the number of equal parts the beat is split into
+    pub fn division(&self) -> usize {
+        self.division
+    }
+
     /// Calculates the length (i.e. number of swars) contained in it
     pub fn len(&self) -> usize {
         self.swars.len()
     }
 
-    /// Derive the lower octave equivalent of a swarbeat
-    pub fn lower(&self) -> SwarBeat {
+    /// Derive the lower octave equivalent of a swarbeat, if all its swars
+    /// can be played lower
+    pub fn lower(&self) -> Option<SwarBeat> {
         let mut lower_swars = Vec::<Swar>::new();
         for sw in &self.swars {
-            let lower_swar = sw.lower();
+            let lower_swar = sw.lower()?;
             lower_swars.push(lower_swar);
         }
 
-        SwarBeat::new(lower_swars)
+        Some(SwarBeat::divided(lower_swars, self.division, self.offset))
     }
 
-    /// Derive the higher octave equivalent of a swarbeat
-    pub fn higher(&self) -> SwarBeat {
+    /// Derive the higher octave equivalent of a swarbeat, if all its swars
+    /// can be played higher
+    pub fn higher(&self) -> Option<SwarBeat> {
         let mut higher_swars = Vec::<Swar>::new();
         for sw in &self.swars {
-            let higher_swar = sw.higher();
+            let higher_swar = sw.higher()?;
             higher_swars.push(higher_swar);
         }
 
-        SwarBeat::new(higher_swars)
+        Some(SwarBeat::divided(higher_swars, self.division, self.offset))
     }
 
     pub fn random_swar(&self) -> Swar {
@@ -69,7 +100,7 @@ impl SwarBeat {
         swars.insert(index, swar);
     }
 
-    pub fn increment_swar_at(&mut self, index: usize, beat_count_inc: f32) {
+    pub fn increment_swar_at(&mut self, index: usize, beat_count_inc: Beats) {
          if let Some(swar) =  self.swars.get_mut(index) {
               swar.inc_beat_count(beat_count_inc);
          }
@@ -78,9 +109,7 @@ impl SwarBeat {
     /// Find the first swar with a pitch traversing back from index `from`
     pub fn swar_index_reverse_from(&self, from: usize) -> Option<(usize, &Swar)> {
         for j in (0..from+1).rev() {
-            println!("  j: {}", j);
             if let Some(swar) = self.swars.get(j) {
-                println!("  swar: {}", swar);
                 if let Some(_) = &swar.pitch {
                     return Some((j, &swar));
                 }
@@ -102,9 +131,7 @@ impl SwarBeat {
     /// Find the first swar with a pitch traversing forward from index `from`
     pub fn swar_index_forward_from(&self, from: usize) -> Option<(usize, &Swar)> {
         for j in from..self.len() {
-            println!("  j: {}", j);
             if let Some(swar) = self.swars.get(j) {
-                println!("  swar: {}", swar);
                 if let Some(_) = &swar.pitch {
                     return Some((j, &swar));
                 }
@@ -126,42 +153,27 @@ impl SwarBeat {
 
 
 impl fmt::Display for SwarBeat {
-    // there are four options: S, S:S, S:S:S:S, S/G
+    // a swar in each part of the beat, S:R:G, with a `-` for a part the swar
+    // before is held over (-:R:G or S:-:G); or a kan swar, P/M
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         // if there no swars in that swarbeat, it has a "-"? meaning it is an extension
         // of previous swar?
-        let mut s = String::from("-");
-        match self.swars.first() {
-             Some(first_swar) => {
-                 let rest_swars = self.swars.get(1..).unwrap();
-                 match rest_swars.len() {
-                     0 => {
-                         // M:M -:P
-                         // the second swarbeat has only one swar (0.5 beat) but rendered as 'P'
-                         // so we count the number of beats, and if < 1.0 then we add a ':'
-                         if first_swar.beat_cnt < 1.0 {
-                             s = format!("-:{}", first_swar);
-                         } else {
-                             s = format!("{}", first_swar);
-                         }
-                     },
-                     _ => {
-                         let mut prev_bt_cnt = first_swar.beat_cnt;
-                         s = format!("{}", first_swar);
-                         for swar in rest_swars {
-                             // when we have the rest of the swars with fraction beat
-                             if prev_bt_cnt == KAN_SWAR_BEAT_COUNT {
-                                 s = format!("{}/{}", s, swar);
-                             } else {
-                                 s = format!("{}:{}", s, swar);
-                             }
-                             prev_bt_cnt = swar.beat_cnt;
-                         }
-                     }
-                 }
-             },
-            _ => {}
-        }
+        let s = match &self.swars[..] {
+            [] => String::from("-"),
+            [kan, swar] if kan.beat_cnt == KAN_SWAR_BEAT_COUNT => format!("{}/{}", kan, swar),
+            [swar] if self.division == 1 => format!("{}", swar),
+            swars => {
+                let mut parts = vec![String::from("-"); self.division];
+                let mut at = self.offset;
+                for swar in swars {
+                    if let Some(part) = parts.get_mut(at) {
+                        *part = swar.to_string();
+                    }
+                    at += swar.beat_cnt.parts(self.division).unwrap_or(1).max(1);
+                }
+                parts.join(":")
+            },
+        };
 
         write!(f, "{}", s)
     }
@@ -169,18 +181,19 @@ impl fmt::Display for SwarBeat {
 
 #[cfg(test)]
 mod tests {
-    use crate::raagas::sound::Pitch;
+    use crate::raagas::pitch::Pitch;
     use crate::raagas::swars::Swar;
     use crate::raagas::swarbeat::SwarBeat;
     use crate::raagas::constants::KAN_SWAR_BEAT_COUNT;
+    use crate::raagas::beats::Beats;
 
     /// test a swarbeat with a pair of swars
     #[test]
     fn test_swarbeat_repr_double() {
         let sa_pitch = Pitch::new("S".to_string());
         let ma_pitch = Pitch::new("M".to_string());
-        let sa = Swar::new(sa_pitch, 0.5);
-        let ma = Swar::new(ma_pitch, 0.5);
+        let sa = Swar::new(sa_pitch, Beats::new(1, 2));
+        let ma = Swar::new(ma_pitch, Beats::new(1, 2));
         let mut swars = Vec::<Swar>::new();
         swars.push(sa);
         swars.push(ma);
@@ -195,10 +208,10 @@ mod tests {
         let re_pitch = Pitch::new("R".to_string());
         let ma_pitch = Pitch::new("M".to_string());
         let pa_pitch = Pitch::new("P".to_string());
-        let sa = Swar::new(sa_pitch, 0.25);
-        let re = Swar::new(re_pitch, 0.25);
-        let ma = Swar::new(ma_pitch, 0.25);
-        let pa = Swar::new(pa_pitch, 0.25);
+        let sa = Swar::new(sa_pitch, Beats::new(1, 4));
+        let re = Swar::new(re_pitch, Beats::new(1, 4));
+        let ma = Swar::new(ma_pitch, Beats::new(1, 4));
+        let pa = Swar::new(pa_pitch, Beats::new(1, 4));
         let mut swars = Vec::<Swar>::new();
         swars.push(sa);
         swars.push(re);
@@ -214,7 +227,7 @@ mod tests {
         let ni_pitch = Pitch::new(".N".to_string());
         let sa_pitch = Pitch::new("S".to_string());
         let ni = Swar::new(ni_pitch, KAN_SWAR_BEAT_COUNT);
-        let sa = Swar::new(sa_pitch, 1.0 - KAN_SWAR_BEAT_COUNT);
+        let sa = Swar::new(sa_pitch, Beats::ONE - KAN_SWAR_BEAT_COUNT);
         let mut swars = Vec::<Swar>::new();
         swars.push(ni);
         swars.push(sa);
@@ -225,14 +238,26 @@ mod tests {
     /// test swarbeat representation with half empty beat
     #[test]
     fn test_swarbeat_repr_half_empty_beat() {
-        let empty_pitch = Pitch::new("".to_string());
         let sa_pitch = Pitch::new("S".to_string());
-        let empty = Swar::new(empty_pitch, 0.5);
-        let sa = Swar::new(sa_pitch, 0.5);
+        let empty = Swar::empty(Beats::new(1, 2));
+        let sa = Swar::new(sa_pitch, Beats::new(1, 2));
         let mut swars = Vec::<Swar>::new();
         swars.push(empty);
         swars.push(sa);
         let sw_bt: SwarBeat = SwarBeat::new(swars);
         assert_eq!(sw_bt.to_string(), ":S");
     }
+
+    /// test a triplet, with the swar before held over and within the beat
+    #[test]
+    fn test_swarbeat_repr_triplet() {
+        let swar = |s: &str, beat_cnt| Swar::new(Pitch::new(s.to_string()), beat_cnt);
+        let sw_bt = SwarBeat::new(vec![swar("S", Beats::new(1, 3)), swar("R", Beats::new(1, 3)), swar("G", Beats::new(1, 3))]);
+        assert_eq!(sw_bt.division(), 3);
+        assert_eq!(sw_bt.to_string(), "S:R:G");
+        let sw_bt = SwarBeat::new(vec![swar("R", Beats::new(1, 3)), swar("G", Beats::new(1, 3))]);
+        assert_eq!(sw_bt.to_string(), "-:R:G");
+        let sw_bt = SwarBeat::divided(vec![swar("S", Beats::new(2, 3)), swar("G", Beats::new(1, 3))], 3, 0);
+        assert_eq!(sw_bt.to_string(), "S:-:G");
+    }
 }
\ No newline at end of file
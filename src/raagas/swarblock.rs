@@ -1,10 +1,12 @@
-use crate::raagas::swars::{Swar, BeatSrc};
+use crate::raagas::swars::{Swar, Ornament};
 use crate::raagas::swarbeat::SwarBeat;
 use std::fmt;
-use crate::raagas::sound::{AudioDevice, Pitch};
-use rodio::{PlayError, Sink};
-use crate::raagas::utils;
-use crate::raagas::constants::{BPS, KAN_SWAR_BEAT_COUNT};
+use std::str::FromStr;
+use crate::raagas::sound::AudioDevice;
+use crate::raagas::pitch::Pitch;
+use crate::raagas::notation::{self, ParseError, ParseErrorKind, Phrases};
+use crate::raagas::constants::{KAN_SWAR_BEAT_COUNT, ANDOLAN_DEPTH, ANDOLAN_RATE};
+use crate::raagas::beats::Beats;
 use rand::Rng;
 
 #[derive(Debug, Clone)]
@@ -23,24 +25,28 @@ impl SwarBlock {
         self.0.len()
     }
 
-    pub fn lower(&self) -> SwarBlock {
+    /// Returns the swarblock an octave lower; `None` if any of its swars is
+    /// in ati-mandra
+    pub fn lower(&self) -> Option<SwarBlock> {
         let mut lower = Vec::<SwarBeat>::new();
         for bt in &self.0 {
-            let lower_bt = bt.lower();
+            let lower_bt = bt.lower()?;
             lower.push(lower_bt);
         }
 
-        SwarBlock(lower)
+        Some(SwarBlock(lower))
     }
 
-    pub fn higher(&self) -> SwarBlock {
+    /// Returns the swarblock an octave higher; `None` if any of its swars is
+    /// in ati-taar
+    pub fn higher(&self) -> Option<SwarBlock> {
         let mut higher = Vec::<SwarBeat>::new();
         for bt in &self.0 {
-            let higher_bt = bt.higher();
+            let higher_bt = bt.higher()?;
             higher.push(higher_bt);
         }
 
-        SwarBlock(higher)
+        Some(SwarBlock(higher))
     }
 
     /// Retrieve the first (i, j) of match_swar, where
@@ -146,41 +152,95 @@ impl SwarBlock {
         None
     }
 
-    pub fn build_sink(&self,
-                      beat_src: &Option<BeatSrc>,
-                      dev: &AudioDevice) -> Result<Vec<Option<Sink>>, PlayError> {
-        let mut sinks: Vec<Option<Sink>> = Vec::new();
-        for sw_bt in &self.0 {
-            for sw in &sw_bt.swars {
-                let bt_sink = sw.build_sink(&beat_src, &dev)?;
-                sinks.push(bt_sink);
-            }
-        }
+    pub fn play(&self, dev: &AudioDevice) {
+        dev.play(self);
+    }
+}
 
-        Ok(sinks)
+impl SwarBlock {
+    /// Appends the swars of the notation `s`, e.g. `S - R:G P/M`; a `-` at
+    /// its start extends the last swar already in the swarblock
+    pub fn push_str(&mut self, s: &str) -> Result<(), ParseError> {
+        self.push_notation(s, &Phrases::new())
     }
 
-    pub fn play(&self, dev: &AudioDevice) {
-        for sw_bt in &self.0 {
-            for sw in &sw_bt.swars {
-                let sw_sink = sw.build_sink(&None, &dev).unwrap();
-                match sw_sink {
-                    Some(sink) => {
-                        sink.play();
-                        utils::delay(sw.beat_cnt * BPS);
-                        sink.stop();
-                    },
-                    None => {
-                        utils::delay(sw.beat_cnt * BPS);
+    /// Appends the swars of the notation `s`, with its groups repeated, e.g.
+    /// `(S R G R)x3`, and the `phrases` it refers to, e.g. `$mukhda`, played
+    /// in their place
+    pub fn push_notation(&mut self, s: &str, phrases: &Phrases) -> Result<(), ParseError> {
+        for (col, sw_bt) in notation::expand(s, phrases)? {
+            let sw_bt = sw_bt.as_str();
+            let mut swars = Vec::<Swar>::new();
+            // the number of equal parts the beat is split into, and the parts
+            // the swar before is held over for
+            let mut division = 1;
+            let mut offset = 0;
+            if sw_bt.eq("-") {
+                // S:G -  (G will be a beat and a half)
+                // S - -  Go all the way back to S and extend it by 1 beat
+                // and nothing to add for current swarbeat
+                if !extend_last_swar(&mut self.0, Beats::ONE) {
+                    return Err(ParseError::new(ParseErrorKind::NothingToExtend, sw_bt, col));
+                }
+            } else if sw_bt.contains(':') {
+                // the beat is split evenly between its swars, e.g. halves
+                // (S:R, -:S, :S, S:), triplets (S:R:G) or quarters (S:R:G:M);
+                // a blank is a pause and a `-` holds on the swar before it
+                let parts = notation::split_with_offsets(sw_bt, ':');
+                division = parts.len();
+                let beat_cnt = Beats::new(1, division as i64);
+                for (at, sw) in parts {
+                    if sw.eq("-") {
+                        let extended = match swars.last_mut() {
+                            Some(prev) => {
+                                prev.inc_beat_count(beat_cnt);
+                                true
+                            },
+                            _ => {
+                                offset += 1;
+                                extend_last_swar(&mut self.0, beat_cnt)
+                            },
+                        };
+                        if !extended {
+                            return Err(ParseError::new(ParseErrorKind::NothingToExtend, sw, col + at));
+                        }
+                    } else if sw.is_empty() {
+                        swars.push(Swar::empty(beat_cnt));
+                    } else {
+                        swars.push(parse_swar(sw, beat_cnt).map_err(|e| e.shifted(col - 1 + at))?);
                     }
                 }
+            } else if sw_bt.contains('/') {
+                // kan swar, e.g.: P/M
+                match notation::split_with_offsets(sw_bt, '/')[..] {
+                    [(_, kan), (at, main)] if !kan.is_empty() && !main.is_empty() => {
+                        let kan_bt_cnt = KAN_SWAR_BEAT_COUNT;
+                        swars.push(parse_swar(kan, kan_bt_cnt).map_err(|e| e.shifted(col - 1))?);
+                        let main_swar = parse_swar(main, Beats::ONE - kan_bt_cnt)
+                            .map_err(|e| e.shifted(col - 1 + at))?;
+                        swars.push(main_swar);
+                    },
+                    _ => return Err(ParseError::new(ParseErrorKind::Kan, sw_bt, col)),
+                }
+            } else {
+                // all else, just a plain swar (e.g.: S)
+                swars.push(parse_swar(sw_bt, Beats::ONE).map_err(|e| e.shifted(col - 1))?);
             }
+            self.0.push(SwarBeat::divided(swars, division, offset));
         }
+
+        Ok(())
     }
 }
 
 impl fmt::Display for SwarBlock {
+    /// Writes the notation of the swarblock; `{:#}` writes repeated swarbeats
+    /// as a group, e.g. `(S R G R)x3`
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            let beats: Vec<String> = self.0.iter().map(|sw_bt| sw_bt.to_string()).collect();
+            return write!(f, "{}", notation::compress(&beats));
+        }
         let mut s = String::new();
         if let Some(first_sw_bt) = &self.0.first() {
             s = format!("{}{}", s, first_sw_bt);
@@ -205,92 +265,58 @@ impl From<Vec<Swar>> for SwarBlock {
     }
 }
 
-impl From<&str> for SwarBlock {
+impl FromStr for SwarBlock {
+    type Err = ParseError;
+
     /// Return a SwarBlock from a string denoting sequence of swars
-    fn from(s: &str) -> Self {
-            let mut swarbeats_vec: Vec<SwarBeat> = vec![];
-            let swarbeats: Vec<String> = s.trim().split(" ").map(|x| x.to_string()).collect();
-            for sw_bt in swarbeats {
-                let mut swars = Vec::<Swar>::new();
-                if sw_bt.eq("-") {
-                    // S:G -  (G will be a beat and a half)
-                    // S - -  Go all the way back to S and extend it by 1 beat
-                    // add an extra beat to the previous swarbeat
-                    // and nothing to add for current swarbeat
-                    extend_last_swar(&mut swarbeats_vec, 1.0);
-                } else {
-                    if sw_bt.contains(":") {
-                        // four cases: either two swars or four swars
-                        // two swars: S:S, -:S :S S: we don't need 'S:- M' as we can always write it as
-                        // 'S:M -'
-                        // four swars: S:S:S:S
-                        let sw_bts_vec: Vec<String> = sw_bt.split(":").map(|x| x.to_string()).collect();
-                        if sw_bts_vec.len() == 2 {
-                            // first and second swar are each 0.5 beat
-                            let first_swar_s = sw_bts_vec.first().unwrap();
-                            let last_swar_s = sw_bts_vec.last().unwrap();
-                            if first_swar_s.eq("-") {
-                                // modify last swar of previous SwarBeat
-                                extend_last_swar(&mut swarbeats_vec, 0.5);
-                                // and last_swar to swars
-                                let last_swar = Swar::new(Pitch::new(last_swar_s.to_string()), 0.5);
-                                swars.push(last_swar);
-                            } else if last_swar_s.eq("-") {
-                                // add the first swar
-                                let first_swar = Swar::new(Pitch::new(last_swar_s.to_string()), 1.0);
-                                swars.push(first_swar);
-                            } else {
-                                if first_swar_s.eq("") {
-                                    swars.push(Swar::empty(0.5));
-                                } else {
-                                    swars.push(Swar::new(
-                                        Pitch::new(first_swar_s.to_string()),
-                                        0.5)
-                                    );
-                                }
-                                if last_swar_s.eq("") {
-                                    swars.push(Swar::empty(0.5));
-                                } else {
-                                    swars.push(Swar::new(
-                                        Pitch::new(last_swar_s.to_string()),
-                                        0.5)
-                                    );
-                                }
-                            }
-                        } else if sw_bts_vec.len() == 4 {
-                            // each of the four swars are 0.25 beat
-                            let beat_count = 0.25;
-                            for sw in sw_bts_vec {
-                                if sw.eq("-")  {
-                                    extend_last_swar(&mut swarbeats_vec, beat_count);
-                                } else {
-                                    let swar = Swar::new(
-                                        Pitch::new(sw.to_string()), beat_count
-                                    );
-                                    swars.push(swar);
-                                }
-                            }
-                        }
-                    } else if sw_bt.contains("/") {
-                        // kan swar
-                        // e.g.: P/M
-                        let swrs: Vec<String> = sw_bt.split("/").map(|x| x.to_string()).collect();
-                        let kan = swrs.get(0).unwrap();
-                        let kan_bt_cnt: f32 = 1.0 * KAN_SWAR_BEAT_COUNT;
-                        swars.push(Swar::new(Pitch::new(kan.to_string()), kan_bt_cnt));
-                        let main_swar = swrs.get(1).unwrap();
-                        swars.push(Swar::new(Pitch::new(main_swar.to_string()), 1.0 - kan_bt_cnt));
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut blk = SwarBlock(Vec::new());
+        blk.push_str(s)?;
 
-                    } else {
-                        // all else, just a plain swar (e.g.: S)
-                        swars.push(Swar::new(Pitch::new(sw_bt.to_string()), 1.0));
-                    }
-                }
-                swarbeats_vec.push(SwarBeat::new(swars));
-            }
+        Ok(blk)
+    }
+}
 
-            SwarBlock(swarbeats_vec)
+impl From<&str> for SwarBlock {
+    /// Return a SwarBlock from a string denoting sequence of swars, panicking
+    /// if the notation is invalid (`parse` returns the error instead)
+    fn from(s: &str) -> Self {
+        s.parse().unwrap_or_else(|e| panic!("Invalid notation {:?}: {}", s, e))
+    }
+}
+
+/// Returns a swar from its notation, e.g. `S`, `.n`, a meend `G~R` or an
+/// andolan `g^`, `g^40` (depth in cents) or `g^40@0.5` (and rate in Hz);
+/// the column of an error is from the start of the swar
+fn parse_swar(s: &str, beat_cnt: Beats) -> Result<Swar, ParseError> {
+    if let Some((from, to)) = s.split_once('~') {
+        let pitch = parse_pitch(from, 1)?;
+        if to.is_empty() {
+            return Err(ParseError::new(ParseErrorKind::Meend, s, 1));
+        }
+        let to = parse_pitch(to, from.chars().count() + 2)?;
+        return Ok(Swar::new(pitch, beat_cnt).with_ornament(Ornament::Meend(to)));
+    }
+    if let Some((swar, andolan)) = s.split_once('^') {
+        let pitch = parse_pitch(swar, 1)?;
+        let (depth, rate) = andolan.split_once('@').unwrap_or((andolan, ""));
+        let value = |v: &str, default: f32| match v {
+            "" => Ok(default),
+            _ => v.parse::<f32>().map_err(|_| ParseError::new(ParseErrorKind::Andolan, s, 1)),
+        };
+        let andolan = Ornament::Andolan {
+            depth: value(depth, ANDOLAN_DEPTH)?,
+            rate: value(rate, ANDOLAN_RATE)?,
+        };
+        return Ok(Swar::new(pitch, beat_cnt).with_ornament(andolan));
     }
+
+    Ok(Swar::new(parse_pitch(s, 1)?, beat_cnt))
+}
+
+// returns the pitch of a swar at `column`, if it is a swar of any saptak
+fn parse_pitch(s: &str, column: usize) -> Result<Pitch, ParseError> {
+    s.parse::<Pitch>().map_err(|_| ParseError::new(ParseErrorKind::UnknownSwar, s, column))
 }
 
 // traversing from the last item, returns the index of the swarbeat with a swar in it
@@ -308,18 +334,26 @@ fn get_last_swarbeat_with_swar(swarbeats: &mut Vec<SwarBeat>) -> Option<usize> {
     None
 }
 
-// increment the last swar of previous swarbeat
-fn extend_last_swar(swarbeats: &mut Vec<SwarBeat>, beat_count_inc: f32) {
+// increment the last swar of previous swarbeat, returning false if there is
+// no swar to extend
+fn extend_last_swar(swarbeats: &mut Vec<SwarBeat>, beat_count_inc: Beats) -> bool {
     if let Some(i) = get_last_swarbeat_with_swar(swarbeats) {
         if let Some(prev_sw_bt) = swarbeats.get_mut(i) {
             prev_sw_bt.increment_swar_at(prev_sw_bt.len()-1, beat_count_inc);
+            return true;
         }
     }
+
+    false
 }
 
 #[cfg(test)]
 mod tests {
     use crate::raagas::swarblock::{SwarBlock, extend_last_swar, get_last_swarbeat_with_swar};
+    use crate::raagas::swars::Ornament;
+    use crate::raagas::pitch::Pitch;
+    use crate::raagas::notation::{ParseErrorKind, Phrases};
+    use crate::raagas::beats::Beats;
 
     /// test no of swarbeats match for a sequence
     #[test]
@@ -335,11 +369,11 @@ mod tests {
         let s = "S - G -";
         let blk = SwarBlock::from(s);
         let mut swarbeats = blk.0;
-        extend_last_swar(&mut swarbeats, 1.0);
+        extend_last_swar(&mut swarbeats, Beats::ONE);
         let i = get_last_swarbeat_with_swar(&mut swarbeats).unwrap();
         let sw_bt = swarbeats.get(i).unwrap();
         let last_swar = sw_bt.swars.last().unwrap();
-        assert_eq!(last_swar.beat_cnt, 3.0);
+        assert_eq!(last_swar.beat_cnt, Beats::whole(3));
     }
 
     /// test if the beat count matches when half a beat swar is extended
@@ -348,11 +382,11 @@ mod tests {
         let s = "S - :G";
         let blk = SwarBlock::from(s);
         let mut swarbeats = blk.0;
-        extend_last_swar(&mut swarbeats, 1.0);
+        extend_last_swar(&mut swarbeats, Beats::ONE);
         let i = get_last_swarbeat_with_swar(&mut swarbeats).unwrap();
         let sw_bt = swarbeats.get(i).unwrap();
         let last_swar = sw_bt.swars.last().unwrap();
-        assert_eq!(last_swar.beat_cnt, 1.5);
+        assert_eq!(last_swar.beat_cnt, Beats::new(3, 2));
     }
 
     /// test rendering of a swar spanning two swarbeats, e.g.: M:M -:P
@@ -371,4 +405,79 @@ mod tests {
         let blk: SwarBlock = SwarBlock::from(s);
         assert_eq!(blk.to_string(), "S:R:M:P S - - M P:P -:D :D P/M");
     }
-}
\ No newline at end of file
+
+    /// test rendering of swarbeats split into 3, 6 and 8 swars, with pauses and
+    /// swars held over within and into them
+    /// S:R:G - -:M:P D:N:S.:R.:G.:M. :S:R:G:M:P:D:N S:-:G
+    #[test]
+    fn test_load_subdivided_swarbeats() {
+        let s = "S:R:G - -:M:P D:N:S.:R.:G.:M. :S:R:G:M:P:D:N S:-:G";
+        let blk: SwarBlock = SwarBlock::from(s);
+        assert_eq!(blk.to_string(), s);
+        let swars = blk.to_swars();
+        // G is held for its third of a beat, the next beat and a third more
+        assert_eq!(swars[2].beat_cnt, Beats::new(5, 3));
+        assert_eq!(swars[5].beat_cnt, Beats::new(1, 6));
+        assert!(swars[11].pitch.is_none());
+        assert_eq!(swars[12].beat_cnt, Beats::new(1, 8));
+        assert_eq!(swars[19].beat_cnt, Beats::new(2, 3));
+        let beats: Beats = swars.iter().map(|sw| sw.beat_cnt).sum();
+        assert_eq!(beats, Beats::whole(6));
+    }
+
+    /// test notation errors give the offending token and its column
+    #[test]
+    fn test_parse_errors() {
+        let e = "S R:X P".parse::<SwarBlock>().unwrap_err();
+        assert_eq!((e.kind, e.token.as_str(), e.column), (ParseErrorKind::UnknownSwar, "X", 5));
+        let e = "- S".parse::<SwarBlock>().unwrap_err();
+        assert_eq!((e.kind, e.column), (ParseErrorKind::NothingToExtend, 1));
+        let e = "S P/M/G".parse::<SwarBlock>().unwrap_err();
+        assert_eq!((e.kind, e.token.as_str()), (ParseErrorKind::Kan, "P/M/G"));
+        assert_eq!("S G~".parse::<SwarBlock>().unwrap_err().kind, ParseErrorKind::Meend);
+        assert_eq!("g^x".parse::<SwarBlock>().unwrap_err().kind, ParseErrorKind::Andolan);
+        let e = "S .N~Q".parse::<SwarBlock>().unwrap_err();
+        assert_eq!((e.token.as_str(), e.column), ("Q", 6));
+    }
+
+    /// test meends within plain, half beat and extended swars
+    #[test]
+    fn test_load_meend() {
+        let s = ".N~R - G~S:R P";
+        let blk: SwarBlock = SwarBlock::from(s);
+        assert_eq!(blk.to_string(), ".N~R - G~S:R P");
+        let swars = blk.to_swars();
+        assert_eq!(swars[0].beat_cnt, Beats::whole(2));
+        assert_eq!(swars[0].ornament(), Some(&Ornament::Meend(Pitch::new("R".to_string()))));
+        assert_eq!(swars[1].beat_cnt, Beats::new(1, 2));
+        assert!(swars[3].ornament().is_none());
+    }
+
+    /// test andolans round-trip with their depth and rate
+    #[test]
+    fn test_load_andolan() {
+        let s = ".n^ S g^40 - R:g^40@0.5";
+        let blk: SwarBlock = SwarBlock::from(s);
+        assert_eq!(blk.to_string(), ".n^ S g^40 - R:g^40@0.5");
+        let swars = blk.to_swars();
+        assert_eq!(swars[0].ornament(), Some(&Ornament::Andolan { depth: 30.0, rate: 1.0 }));
+        assert_eq!(swars[2].beat_cnt, Beats::whole(2));
+        assert_eq!(swars[4].ornament(), Some(&Ornament::Andolan { depth: 40.0, rate: 0.5 }));
+    }
+
+    /// test groups are repeated and phrases played in their place, and
+    /// repetitions written back as groups
+    #[test]
+    fn test_load_groups_and_phrases() {
+        let mut phrases = Phrases::new();
+        phrases.insert("mukhda".to_string(), "G:M P".to_string());
+        let mut blk = SwarBlock(Vec::new());
+        blk.push_notation("(S R G R)x3 $mukhda -", &phrases).unwrap();
+        assert_eq!(blk.to_string(), "S R G R S R G R S R G R G:M P -");
+        assert_eq!(format!("{:#}", blk), "(S R G R)x3 G:M P -");
+        assert_eq!(blk.to_swars()[14].beat_cnt, Beats::whole(2));
+
+        let e = blk.push_notation("S $antra", &phrases).unwrap_err();
+        assert_eq!((e.kind, e.column), (ParseErrorKind::UnknownPhrase, 3));
+    }
+}
@@ -3,8 +3,10 @@ use crate::raagas::swarbeat::SwarBeat;
 use crate::raagas::swars::Swar;
 use rand::Rng;
 use crate::raagas::constants::KAN_SWAR_BEAT_COUNT;
+use crate::raagas::beats::Beats;
 use crate::raagas::{Mutate, MutationOperators};
 use crate::raagas::sound::AudioDevice;
+use crate::raagas::notation;
 use std::fmt;
 use rand::seq::SliceRandom;
 
@@ -14,26 +16,28 @@ pub struct SwarBlocks(pub Vec<SwarBlock>);
 
 impl SwarBlocks {
 
-    /// return the lower octave equivalent of the swars
-    pub fn lower(&self) -> SwarBlocks {
+    /// return the lower octave equivalent of the swars, if they can all be
+    /// played lower
+    pub fn lower(&self) -> Option<SwarBlocks> {
         let mut lower_blks = Vec::<SwarBlock>::new();
         for blk in &self.0 {
-            let lower_blk = blk.lower();
+            let lower_blk = blk.lower()?;
             lower_blks.push(lower_blk);
         }
 
-        SwarBlocks(lower_blks)
+        Some(SwarBlocks(lower_blks))
     }
 
-    /// return the higher octave equivalent of the swars
-    pub fn higher(&self) -> SwarBlocks {
+    /// return the higher octave equivalent of the swars, if they can all be
+    /// played higher
+    pub fn higher(&self) -> Option<SwarBlocks> {
         let mut higher_blks = Vec::<SwarBlock>::new();
         for blk in &self.0 {
-            let higher_blk = blk.higher();
+            let higher_blk = blk.higher()?;
             higher_blks.push(higher_blk);
         }
 
-        SwarBlocks(higher_blks)
+        Some(SwarBlocks(higher_blks))
     }
 
     /// Retrieve the first index of swar matched.
@@ -185,6 +189,11 @@ impl SwarBlocks {
         swars
     }
 
+    /// Returns the number of beats the swarblocks are played over
+    pub fn beats(&self) -> Beats {
+        self.to_swars_as_ref().iter().map(|sw| sw.beat_cnt).sum()
+    }
+
     /// Returns the adjacent swars surrounding the index.
     pub fn adjacent_swars(&self, index: &SwarInSwarBlock) -> Option<Vec<&Swar>> {
         println!("index: {:?}", index);
@@ -227,9 +236,7 @@ impl SwarBlocks {
     }
 
     pub fn play(&self, dev: &AudioDevice) {
-        for blk in &self.0 {
-            blk.play(&dev);
-        }
+        dev.play(self);
     }
 
     pub fn get_swarbeat_mut_at(&mut self, index: usize) -> Option<&mut SwarBeat> {
@@ -274,7 +281,15 @@ impl From<Vec<Swar>> for SwarBlocks {
 }
 
 impl fmt::Display for SwarBlocks {
+    /// Writes the notation of the swarblocks; `{:#}` writes repeated swarbeats
+    /// as a group, e.g. `(S R G R)x3`
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            let beats: Vec<String> = self.0.iter()
+                .flat_map(|blk| blk.0.iter().map(|sw_bt| sw_bt.to_string()))
+                .collect();
+            return write!(f, "{}", notation::compress(&beats));
+        }
         let mut s = String::new();
         if let Some(first_blk) = self.0.first() {
             s = format!("{}{}", s, first_blk);
@@ -316,14 +331,14 @@ impl Mutate for SwarBlocks {
                 // mut_sw_blk.replace_swar_with_beat_change(&index, &rnd_swar, 1.0);
                 if let Some(sw_bt) = mut_sw_blk.get_swarbeat_mut_at(index.swarbeat_index) {
                     let mut rnd_swar = rnd_swar.clone();
-                    rnd_swar.inc_beat_count(1.0);
+                    rnd_swar.inc_beat_count(Beats::ONE);
                     sw_bt.replace_swar(index.swar_index, rnd_swar);
                 }
             },
             "dec_beat" => {
                 let mut dec_bt = rnd_swar.beat_cnt;
-                if dec_bt >= 1.0 {
-                    dec_bt = dec_bt/2.0;
+                if dec_bt >= Beats::ONE {
+                    dec_bt = dec_bt / 2;
                 }
                 if let Some(sw_bt) = mut_sw_blk.get_swarbeat_mut_at(index.swarbeat_index) {
                     let mut rnd_swar = rnd_swar.clone();
@@ -337,9 +352,9 @@ impl Mutate for SwarBlocks {
                 // TODO: ensure the beat is a full beat, how to handle beat: S:G:M:P
 
                 let mut rnd_swar_a = rnd_swar.clone();
-                rnd_swar_a.set_beat_count(0.5);
+                rnd_swar_a.set_beat_count(Beats::new(1, 2));
                 let mut rnd_swar_b = from.choose(&mut rnd).unwrap().to_owned().clone();
-                rnd_swar_b.set_beat_count(0.5);
+                rnd_swar_b.set_beat_count(Beats::new(1, 2));
 
                 // now randomly insert/replace rnd_swar and rnd_swar_latter
                 let b = rand::thread_rng().gen_bool(0.5);
@@ -359,7 +374,7 @@ impl Mutate for SwarBlocks {
                 let mut rnd_swar_a = rnd_swar.clone();
                 rnd_swar_a.set_beat_count(KAN_SWAR_BEAT_COUNT);
                 let mut rnd_swar_b = from.choose(&mut rnd).unwrap().to_owned().clone();
-                rnd_swar_b.set_beat_count(1.0 - KAN_SWAR_BEAT_COUNT);
+                rnd_swar_b.set_beat_count(Beats::ONE - KAN_SWAR_BEAT_COUNT);
 
                 // now randomly insert/replace rnd_swar and rnd_swar_latter
                 let b = rand::thread_rng().gen_bool(0.5);
@@ -408,7 +423,7 @@ mod tests {
         let line = "lineB";
 
         let raag = load::load_yaml(raag, composition).unwrap();
-        let blks = raag.swarmaalika().sthayi.lines.get(line).unwrap();
+        let blks = &raag.swarmaalika().unwrap().sthayi.line(line).unwrap().blks;
         println!("blks: {}", blks);
 
         let from_sw_bt = 12;
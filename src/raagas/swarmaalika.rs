@@ -1,69 +1,129 @@
-use std::collections::HashMap;
-
 use crate::raagas::sound::{AudioDevice};
 
 use crate::raagas::SwarBlocks;
+use crate::raagas::taal::Taal;
+use crate::raagas::laya::{Tempo, TempoMap};
+use crate::raagas::bols::Bols;
+use crate::raagas::bhatkhande::Bhatkhande;
 
+/// The sections of a composition set to a taal: a swarmaalika, or a bandish
+/// of any other form with the taans (or, for a gat, the todas) improvised on
+/// it
 #[derive(Debug, Clone)]
 pub struct Swarmaalika {
     pub mukra: Option<SwarBlocks>,
     pub sthayi: Sthayi,
     pub antara: Antara,
+    pub taans: Vec<Line>,
     pub tihayi: Option<SwarBlocks>,
     sam: usize,
+    taal: Option<Taal>,
+    tempos: TempoMap,
 }
 
+/// A line of a sthayi or antara: its notation, with the label it is known by
+/// (e.g. `lineA`), if it has one, and the bols sung against it, for a bandish
+#[derive(Debug, Clone)]
+pub struct Line {
+    pub blks: SwarBlocks,
+    label: Option<String>,
+    bols: Option<Bols>,
+}
+
+impl Line {
+    pub fn new(blks: SwarBlocks) -> Self {
+        Line { blks, label: None, bols: None }
+    }
+
+    pub fn with_label(mut self, label: &str) -> Self {
+        self.label = Some(label.to_string());
+        self
+    }
+
+    /// Returns the line with `bols` sung against it, checking they fit its
+    /// swarbeats
+    pub fn with_bols(mut self, bols: Bols) -> Result<Self, String> {
+        bols.check(&self.blks)?;
+        self.bols = Some(bols);
+        Ok(self)
+    }
+
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// Returns the bols sung against the line, if it has any
+    pub fn bols(&self) -> Option<&Bols> {
+        self.bols.as_ref()
+    }
+
+    /// Returns the line's label, or else its place in its section (`i` from
+    /// 0), e.g. `line 3`
+    pub fn name(&self, i: usize) -> String {
+        self.label.clone().unwrap_or_else(|| format!("line {}", i + 1))
+    }
+
+    /// Returns the notation of the line to show as it is played, with its
+    /// bols (if any) under it
+    pub fn notation(&self) -> String {
+        match &self.bols {
+            Some(bols) => bols.align(&self.blks)
+                .lines()
+                .map(|l| format!("  {}", l))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            _ => format!("  {}", self.blks),
+        }
+    }
+}
+
+/// The sthayi of a composition, its lines played in order
 #[derive(Debug, Clone)]
 pub struct Sthayi {
-    pub lines: HashMap<String, SwarBlocks>,
+    pub lines: Vec<Line>,
 }
 
 impl Sthayi {
-    pub fn new(lines: HashMap<String, SwarBlocks>) -> Self {
+    pub fn new(lines: Vec<Line>) -> Self {
         Sthayi { lines }
     }
 
-    pub fn play_line(&self, line: &str, no_times: usize, dev: &AudioDevice) {
-        println!("line: {}", line);
-        let line_blks = self.lines.get(line);
-        if let Some(blks) = line_blks {
-            println!("  {}", blks);
-            for _ in 0..no_times {
-                blks.play(&dev);
-            }
-        }
+    /// Returns the line labelled `label`
+    pub fn line(&self, label: &str) -> Option<&Line> {
+        self.lines.iter().find(|l| l.label() == Some(label))
+    }
+
+    pub fn play_line(&self, i: usize, no_times: usize, dev: &AudioDevice) {
+        dev.play_rendered(|r| self.render_line(i, no_times, r));
     }
 
     pub fn play(&self, dev: &AudioDevice) {
-        self.play_line("lineA", 2, &dev);
-        self.play_line("lineB", 2, &dev);
+        dev.play(self);
     }
 }
 
+/// The antara of a composition, its lines played in order
 #[derive(Debug, Clone)]
 pub struct Antara {
-    pub lines: HashMap<String, SwarBlocks>,
+    pub lines: Vec<Line>,
 }
 
 impl Antara {
-    pub fn new(lines: HashMap<String, SwarBlocks>) -> Self {
+    pub fn new(lines: Vec<Line>) -> Self {
         Antara { lines }
     }
 
-    pub fn play_line(&self, line: &str, no_times: usize, dev: &AudioDevice) {
-        println!("line: {}", line);
-        let line_blks = self.lines.get(line);
-        if let Some(blks) = line_blks {
-            println!("  {}", blks);
-            for _ in 0..no_times {
-                blks.play(&dev);
-            }
-        }
+    /// Returns the line labelled `label`
+    pub fn line(&self, label: &str) -> Option<&Line> {
+        self.lines.iter().find(|l| l.label() == Some(label))
+    }
+
+    pub fn play_line(&self, i: usize, no_times: usize, dev: &AudioDevice) {
+        dev.play_rendered(|r| self.render_line(i, no_times, r));
     }
 
     pub fn play(&self, dev: &AudioDevice) {
-        self.play_line("lineC", 2, &dev);
-        self.play_line("lineD", 2, &dev);
+        dev.play(self);
     }
 }
 
@@ -84,20 +144,79 @@ impl Swarmaalika {
             mukra,
             sthayi,
             antara,
+            taans: Vec::new(),
             tihayi,
             sam: my_sam,
+            taal: None,
+            tempos: TempoMap::new(),
         }
     }
 
+    /// Returns the swarmaalika with `taans` played after the antara
+    pub fn with_taans(mut self, taans: Vec<Line>) -> Self {
+        self.taans = taans;
+        self
+    }
+
+    /// Returns the swarmaalika set to `taal`
+    pub fn with_taal(mut self, taal: Option<Taal>) -> Self {
+        self.taal = taal;
+        self
+    }
+
+    /// Returns the swarmaalika with its sections played at `tempos`
+    pub fn with_tempos(mut self, tempos: TempoMap) -> Self {
+        self.tempos = tempos;
+        self
+    }
+
+    /// The beat of the composition that falls on the taal's sam
     pub fn sam(&self) -> usize {
         self.sam
     }
 
+    pub fn taal(&self) -> Option<&Taal> {
+        self.taal.as_ref()
+    }
+
+    /// Returns the tempo `section` is played at, if it has one of its own
+    pub fn tempo(&self, section: &str) -> Option<&Tempo> {
+        self.tempos.get(section)
+    }
+
+    /// Returns the matra of the taal the `beat`th beat (from 1) of the
+    /// composition falls on, e.g. with sam on beat 9 of a teentaal
+    /// composition, it starts on matra 9 (the khali)
+    pub fn matra(&self, beat: usize) -> Option<usize> {
+        let taal = self.taal.as_ref()?;
+        let n = taal.matras();
+        Some((beat + n - self.sam % n) % n + 1)
+    }
+
+    /// Returns the lines of the sthayi, the antara, the taans and the tihayi
+    /// laid out over the taal in Bhatkhande's notation, each starting on the
+    /// matra the composition does; `None` if it isn't set to a taal
+    pub fn notation(&self) -> Option<String> {
+        let layout = Bhatkhande::new(self.taal.as_ref()?).with_from(self.matra(1)?);
+        let mut lines: Vec<(String, &SwarBlocks)> = Vec::new();
+        for (section, section_lines) in [("sthayi", &self.sthayi.lines), ("antara", &self.antara.lines),
+                                         ("taan", &self.taans)] {
+            for (i, line) in section_lines.iter().enumerate() {
+                lines.push((format!("{} {}", section, line.name(i)), &line.blks));
+            }
+        }
+        if let Some(tihayi) = &self.tihayi {
+            lines.push(("tihayi".to_string(), tihayi));
+        }
+
+        let notation: Vec<String> = lines.into_iter()
+            .map(|(name, blks)| format!("{}\n{}", name, layout.swarblocks(blks)))
+            .collect();
+        Some(notation.join("\n\n"))
+    }
+
     pub fn play(&self, dev: &AudioDevice) {
-        self.sthayi.play(&dev);
-        self.sthayi.play_line("lineA", 1, &dev);
-        self.antara.play(&dev);
-        self.sthayi.play_line("lineA", 1, &dev);
+        dev.play(self);
     }
 }
 
@@ -113,8 +232,8 @@ mod tests {
         let line_a = "lineA";
 
         let raag = load::load_yaml(raag, composition).unwrap();
-        let blks = raag.swarmaalika().sthayi.lines.get(line_a);
-        assert!(blks.is_some());
+        let line = raag.swarmaalika().unwrap().sthayi.line(line_a);
+        assert!(line.is_some());
     }
 
     /// test if we can retrieve a line from Sthayi and match a swarbeat
@@ -127,8 +246,37 @@ mod tests {
         let sw_bt_expected = "M:d:M:g";
 
         let raag = load::load_yaml(raag, composition).unwrap();
-        let blks = raag.swarmaalika().sthayi.lines.get(line_a);
-        assert_eq!(blks.unwrap().swarbeats().get(sw_bt_index).unwrap().to_string(), sw_bt_expected);
+        let blks = &raag.swarmaalika().unwrap().sthayi.line(line_a).unwrap().blks;
+        assert_eq!(blks.swarbeats().get(sw_bt_index).unwrap().to_string(), sw_bt_expected);
     }
 
-}
\ No newline at end of file
+    /// test the beats of a composition are placed on the matras of its taal
+    #[test]
+    fn test_swarmaalika_matra() {
+        let raag = load::load_yaml("yaman", "yaman").unwrap();
+        let swarmaalika = raag.swarmaalika().unwrap();
+        assert_eq!(swarmaalika.taal().unwrap().name(), "teentaal");
+        assert_eq!(swarmaalika.matra(1), Some(9));
+        assert_eq!(swarmaalika.matra(9), Some(1));
+        assert_eq!(swarmaalika.matra(16), Some(8));
+        assert_eq!(swarmaalika.matra(17), Some(9));
+
+        let raag = load::load_yaml("durga", "durga").unwrap();
+        assert_eq!(raag.swarmaalika().unwrap().taal().unwrap().matras(), 12);
+        assert_eq!(raag.swarmaalika().unwrap().matra(1), Some(1));
+    }
+
+    /// test the composition is laid out over its taal, starting where it does
+    #[test]
+    fn test_swarmaalika_notation() {
+        let raag = load::load_yaml("yaman", "yeri_aali").unwrap();
+        let notation = raag.swarmaalika().unwrap().notation().unwrap();
+        let rows: Vec<&str> = notation.lines().collect();
+        assert_eq!(rows[0], "sthayi lineA");
+        // sam is on the 9th beat, so the line starts on the khali
+        assert_eq!(rows[1], "        |         | N D P - | - R - S");
+        assert_eq!(rows[2], "X         2         0         3");
+        assert_eq!(rows[3], "G R G G | - - P P");
+        assert!(notation.contains("\n\ntihayi\n"));
+    }
+}
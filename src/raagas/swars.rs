@@ -2,126 +2,199 @@ use std::{fmt, io, fs};
 
 use rodio::decoder::Decoder;
 use rodio::source::{Repeat, TakeDuration};
-use crate::raagas::sound::{Pitch, AudioDevice};
-use rodio::{Sink, Source, PlayError};
+use crate::raagas::pitch::Pitch;
+use rodio::Source;
 
 use crate::raagas::{Mutate, MutationOperators};
 use crate::raagas::swarblock::SwarInSwarBlock;
+use crate::raagas::instrument::Instrument;
+use crate::raagas::envelope::Envelope;
+use crate::raagas::constants::{ANDOLAN_DEPTH, ANDOLAN_RATE};
+use crate::raagas::sound::Hertz;
+use crate::raagas::beats::Beats;
+use std::time::Duration;
 use rand::seq::SliceRandom;
 
 pub type BeatSrc = Repeat<TakeDuration<Decoder<io::BufReader<fs::File>>>>;
 
+/// Ornamentation of a swar
+#[derive(Debug, Clone, PartialEq)]
+pub enum Ornament {
+    /// meend: glide from the swar to this pitch over the swar's duration
+    Meend(Pitch),
+    /// andolan: slow oscillation of `depth` cents either side of the swar,
+    /// `rate` times a second
+    Andolan { depth: f32, rate: f32 },
+}
+
+impl Ornament {
+    /// Returns the lower octave equivalent of the ornament; `None` if it
+    /// glides to a swar in ati-mandra
+    pub fn lower(&self) -> Option<Self> {
+        match self {
+            Ornament::Meend(p) => Some(Ornament::Meend(p.lower()?)),
+            o => Some(o.clone()),
+        }
+    }
+
+    /// Returns the higher octave equivalent of the ornament; `None` if it
+    /// glides to a swar in ati-taar
+    pub fn higher(&self) -> Option<Self> {
+        match self {
+            Ornament::Meend(p) => Some(Ornament::Meend(p.higher()?)),
+            o => Some(o.clone()),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Swar {
     // A blank swar is when there is nothing to play, so a pause
     pub pitch: Option<Pitch>,
-    pub beat_cnt: f32,
+    pub beat_cnt: Beats,
+    pub ornament: Option<Ornament>,
 }
 
 // impl for Swar
 impl Swar {
-    pub fn new(pitch: Pitch, beat_cnt: f32) -> Swar {
+    pub fn new(pitch: Pitch, beat_cnt: Beats) -> Swar {
         Swar {
             pitch: Some(pitch),
             beat_cnt,
+            ornament: None,
         }
     }
 
-    pub fn empty(beat_cnt: f32) -> Self {
+    pub fn empty(beat_cnt: Beats) -> Self {
         Swar {
             pitch: None,
-            beat_cnt
+            beat_cnt,
+            ornament: None,
         }
     }
 
+    /// Returns the swar ornamented with `ornament`
+    pub fn with_ornament(mut self, ornament: Ornament) -> Self {
+        self.ornament = Some(ornament);
+        self
+    }
+
     pub fn pitch(&self) -> Option<Pitch> {
         self.pitch.clone()
     }
 
-    pub fn beat_count(&self) -> f32 {
-        self.beat_cnt
+    pub fn ornament(&self) -> Option<&Ornament> {
+        self.ornament.as_ref()
     }
 
-    pub fn set_beat_count(&mut self, bt_cnt: f32) {
-        self.beat_cnt = bt_cnt;
+    pub fn beat_count(&self) -> Beats {
+        self.beat_cnt
     }
 
-    pub fn freq(&self) -> f64 {
-        self.pitch.as_ref().unwrap().hertz().unwrap().freq()
+    pub fn set_beat_count(&mut self, bt_cnt: Beats) {
+        self.beat_cnt = bt_cnt;
     }
 
-    pub fn lower(&self) -> Self {
-        let p = self.pitch.as_ref().unwrap().to_string();
-        let p_lower = format!(".{}", p);
-        Swar::new(Pitch::new(p_lower), self.beat_cnt)
+    /// Returns the swar an octave lower; `None` if it (or the swar it glides
+    /// to) is in ati-mandra
+    pub fn lower(&self) -> Option<Self> {
+        Some(Swar {
+            pitch: match &self.pitch {
+                Some(p) => Some(p.lower()?),
+                _ => None,
+            },
+            beat_cnt: self.beat_cnt,
+            ornament: match &self.ornament {
+                Some(o) => Some(o.lower()?),
+                _ => None,
+            },
+        })
     }
 
-    pub fn higher(&self) -> Self {
-        let p = self.pitch.as_ref().unwrap().to_string();
-        let p_higher = format!("{}.", p);
-        Swar::new(Pitch::new(p_higher), self.beat_cnt)
+    /// Returns the swar an octave higher; `None` if it (or the swar it glides
+    /// to) is in ati-taar
+    pub fn higher(&self) -> Option<Self> {
+        Some(Swar {
+            pitch: match &self.pitch {
+                Some(p) => Some(p.higher()?),
+                _ => None,
+            },
+            beat_cnt: self.beat_cnt,
+            ornament: match &self.ornament {
+                Some(o) => Some(o.higher()?),
+                _ => None,
+            },
+        })
     }
 
     /// increment the swar beat count by inc
-    pub(crate) fn inc_beat_count(&mut self, inc: f32) {
+    pub(crate) fn inc_beat_count(&mut self, inc: Beats) {
         self.beat_cnt += inc;
     }
 
     /// decrement the swar beat count by dec
-    pub(crate) fn dec_beat_count(&mut self, dec: f32) {
+    pub(crate) fn dec_beat_count(&mut self, dec: Beats) {
         self.beat_cnt -= dec;
     }
 
-    pub(crate) fn build_sink(&self,
-                             beat_src: &Option<BeatSrc>,
-                             dev: &AudioDevice) -> Result<Option<Sink>, PlayError> {
-        let sink = Sink::try_new(&dev.out_stream_handle)?;
-        sink.set_volume(dev.vol());
-        match beat_src {
-            Some(_) => {
-                if let Some(p) = self.pitch.as_ref() {
-                    // play swar with taal
-                    // let sinew = SineWave::from(p.to_owned());
-                    if let Some(sinew) = p.to_sinewave() {
-                        sink.append(sinew);
-                        // sink.append(sinew.mix(Pitch::from_swar("M")));
-                        // sink.append(src.mix(sinew));
-                        return Ok(Some(sink));
-                    }
-                }
-            }
-            _ => {
-                // play swar
-                if let Some(p)  = self.pitch.as_ref() {
-                    if let Some(sinew) = p.to_sinewave() {
-                        // sink.append(sinew);
-                        sink.append(sinew.mix(Pitch::from_swar("M")));
-                        return Ok(Some(sink));
-                    }
+    /// Returns the swar played on `instrument` (mixed with `drone`, if any)
+    /// at the frequencies given by `hertz` (e.g. with Sa at a tonic, in a
+    /// tuning) and shaped by `envelope`, lasting `len` (the length of its beats
+    /// at the tempo played) followed by the release; `None` if the swar is a
+    /// pause.
+    pub(crate) fn source<H: Fn(&Pitch) -> Option<Hertz>>(&self,
+                         len: Duration,
+                         instrument: Instrument,
+                         drone: Option<&Pitch>,
+                         envelope: Envelope,
+                         hertz: H,
+                         sample_rate: u32) -> Option<Box<dyn Source<Item = f32> + Send>> {
+        let hz = self.pitch.as_ref().and_then(&hertz)?;
+        let mut tone = instrument.tone(hz.freq(), sample_rate);
+        match &self.ornament {
+            Some(Ornament::Meend(to)) => {
+                if let Some(to_hz) = hertz(to) {
+                    tone = tone.glide(to_hz.freq(), len);
                 }
-            }
+            },
+            Some(Ornament::Andolan { depth, rate }) => {
+                tone = tone.oscillate(*depth as f64, *rate as f64);
+            },
+            _ => {},
+        }
+        match drone.and_then(&hertz) {
+            Some(drone) => {
+                let drone = Instrument::Sine.tone(drone.freq(), sample_rate);
+                Some(Box::new(envelope.apply(tone.mix(drone), len)))
+            },
+            _ => Some(Box::new(envelope.apply(tone, len))),
         }
-
-        Ok(None)
     }
 }
 
 impl PartialEq for Swar {
+    /// Swars are the same if they are at the same pitch (or both pauses),
+    /// however long they are played
     fn eq(&self, other: &Self) -> bool {
-        let my_freq= self.pitch.as_ref().unwrap().hertz().unwrap().freq();
-        let other_freq= other.pitch.as_ref().unwrap().hertz().unwrap().freq();
-        if my_freq == other_freq {
-            return true;
-        }
-
-        return false;
+        self.pitch == other.pitch
     }
 }
 
 impl fmt::Display for Swar {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let _s = match &self.pitch {
-            Some(p) => format!("{}", p),
+        let _s = match (&self.pitch, &self.ornament) {
+            (Some(p), Some(Ornament::Meend(to))) => format!("{}~{}", p, to),
+            (Some(p), Some(Ornament::Andolan { depth, rate })) => {
+                // only the values differing from the defaults are written out
+                if *rate != ANDOLAN_RATE {
+                    format!("{}^{}@{}", p, depth, rate)
+                } else if *depth != ANDOLAN_DEPTH {
+                    format!("{}^{}", p, depth)
+                } else {
+                    format!("{}^", p)
+                }
+            },
+            (Some(p), _) => format!("{}", p),
             _ => String::new(),
         };
         write!(f, "{}", _s)
@@ -130,14 +203,7 @@ impl fmt::Display for Swar {
 
 /// Returns the index of `swar` from the list of swars `swars`
 pub fn get_swar_index(swars: &Vec<&Swar>, swar: &Swar) -> Option<usize> {
-    for (i, sw) in swars.iter().enumerate() {
-        let swar_freq = swar.freq();
-        if sw.freq() == swar_freq {
-            return Some(i);
-        }
-    }
-
-    None
+    swars.iter().position(|sw| sw.pitch == swar.pitch)
 }
 
 /// Returns true if the sequence of swars `match_swars` are contained in
@@ -187,9 +253,9 @@ impl Mutate for Swar {
 
     fn mutate_swar_duration(&self, _i: usize) -> Option<Swar> {
         let mut mut_swar = self.clone();
-        let beat_durations: Vec<f32> = vec![0.5, 1.0, 2.0, 3.0];
+        let beat_durations: Vec<Beats> = vec![Beats::new(1, 2), Beats::ONE, Beats::whole(2), Beats::whole(3)];
         let mut rnd = rand::thread_rng();
-        mut_swar.beat_cnt = *beat_durations.choose(&mut rnd).unwrap_or_else(|| &(1.0 as f32));
+        mut_swar.beat_cnt = *beat_durations.choose(&mut rnd).unwrap_or(&Beats::ONE);
 
         Some(mut_swar)
     }
@@ -198,16 +264,61 @@ impl Mutate for Swar {
 /// tests on swarbeats and swars
 #[cfg(test)]
 mod tests {
-    use crate::raagas::sound::{Hertz, Pitch};
-    use crate::raagas::swars::Swar;
+    use crate::raagas::sound::Hertz;
+    use crate::raagas::pitch::Pitch;
+    use crate::raagas::constants;
+    use crate::raagas::tuning::Tuning;
+    use crate::raagas::swars::{Swar, Ornament};
+    use crate::raagas::beats::Beats;
 
     /// test S is set to C#, base pitch
     #[test]
     fn test_base_swar_is_sa() {
         let base_hz = Hertz::new(277.18, "C#".to_string());
         let sa_pitch = Pitch::new("S".to_string());
-        let sa = Swar::new(sa_pitch, 1.0);
-        assert_eq!(sa.pitch.unwrap().hertz().unwrap(), base_hz);
+        let sa = Swar::new(sa_pitch, Beats::ONE);
+        assert_eq!(sa.pitch.unwrap().hertz(&constants::tonic(), &Tuning::default()).unwrap(), base_hz);
+    }
+
+    /// test swars move between octaves from any octave, as far as ati-mandra
+    /// and ati-taar
+    #[test]
+    fn test_swar_octaves() {
+        let swar = |s: &str| Swar::new(Pitch::new(s.to_string()), Beats::ONE);
+        assert_eq!(swar(".S").lower().unwrap().to_string(), "..S");
+        assert_eq!(swar("S.").lower().unwrap().to_string(), "S");
+        assert_eq!(swar("M'.").higher().unwrap().to_string(), "M'..");
+        assert_eq!(swar("..P").higher().and_then(|s| s.higher()).and_then(|s| s.higher()).unwrap().to_string(), "P.");
+        assert!(swar("..P").lower().is_none());
+        assert!(swar("S..").higher().is_none());
+        assert_eq!(Pitch::new("..P".to_string()).octave(), -2);
+        assert!(swar(".P").lower().unwrap().pitch.unwrap().hertz(&constants::tonic(), &Tuning::default()).is_some());
+        assert!(".S.".parse::<Pitch>().is_err());
+        assert!(Swar::empty(Beats::ONE).lower().unwrap().pitch.is_none());
+    }
+
+    /// test a meend is rendered as a glide between two swars
+    #[test]
+    fn test_swar_repr_meend() {
+        let ga = Swar::new(Pitch::new("G".to_string()), Beats::ONE)
+            .with_ornament(Ornament::Meend(Pitch::new("R".to_string())));
+        assert_eq!(ga.to_string(), "G~R");
+        assert_eq!(ga.lower().unwrap().to_string(), ".G~.R");
+        // nor can it be played lower if it glides down into ati-mandra
+        let glide = Swar::new(Pitch::new(".S".to_string()), Beats::ONE)
+            .with_ornament(Ornament::Meend(Pitch::new("..N".to_string())));
+        assert!(glide.lower().is_none());
+    }
+
+    /// test an andolan is written out with only its non-default values
+    #[test]
+    fn test_swar_repr_andolan() {
+        let ga = Swar::new(Pitch::new("g".to_string()), Beats::ONE);
+        let andolan = |depth, rate| ga.clone().with_ornament(Ornament::Andolan { depth, rate });
+        assert_eq!(andolan(30.0, 1.0).to_string(), "g^");
+        assert_eq!(andolan(40.0, 1.0).to_string(), "g^40");
+        assert_eq!(andolan(40.0, 0.5).to_string(), "g^40@0.5");
+        assert_eq!(andolan(30.0, 1.0).lower().unwrap().to_string(), ".g^");
     }
 
     /// test swar with single beat
@@ -215,7 +326,7 @@ mod tests {
     fn test_swar_repr_single_beat() {
         // test string version of swar
         let sa_pitch = Pitch::new("S".to_string());
-        let sa = Swar::new(sa_pitch, 1.0);
+        let sa = Swar::new(sa_pitch, Beats::ONE);
         assert_eq!(sa.to_string(), "S");
     }
 }
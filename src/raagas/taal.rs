@@ -1,19 +1,337 @@
-use std::io::BufReader;
-use std::fs::File;
-use rodio::decoder::Decoder;
-use rodio::source::{Repeat, TakeDuration};
-use rodio::Sink;
+use std::fmt;
+use std::path::Path;
+use rodio::Source;
+use rodio::source::UniformSourceIterator;
+use yaml_rust::{Yaml, YamlLoader};
 
+use crate::raagas::constants::CONF_DIR;
+use crate::raagas::beats::Beats;
+
+pub const TAALS: [&str; 6] = ["teentaal", "ektaal", "jhaptaal", "rupak", "dadra", "keherwa"];
+// directory within `CONF_DIR` the taals are read from
+pub(crate) const TAALS_DIR: &str = "taals";
+// how loud the beat is on sam, on a tali, on a khali and on the other matras
+const SAM_ACCENT: f32 = 1.0;
+const TALI_ACCENT: f32 = 0.8;
+const KHALI_ACCENT: f32 = 0.4;
+const MATRA_ACCENT: f32 = 0.6;
+
+/// How the first matra of a vibhag is shown: with a clap (tali) or a wave of
+/// the hand (khali)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Clap {
+    Tali,
+    Khali,
+}
+
+/// A division of the taal, with the bols of the theka for each of its matras
+#[derive(Debug, Clone, PartialEq)]
+pub struct Vibhag {
+    pub clap: Clap,
+    pub bols: Vec<String>,
+}
+
+impl Vibhag {
+    pub fn new(clap: Clap, bols: Vec<String>) -> Self {
+        Vibhag { clap, bols }
+    }
+
+    pub fn matras(&self) -> usize {
+        self.bols.len()
+    }
+}
+
+/// A rhythmic cycle of matras split into vibhags; sam is always its first
+/// matra. Matras are counted from 1, as they are recited.
+#[derive(Debug, Clone, PartialEq)]
 pub struct Taal {
-    taal: Repeat<TakeDuration<Decoder<BufReader<File>>>>,
+    name: String,
+    vibhags: Vec<Vibhag>,
 }
 
 impl Taal {
-    pub fn new(taal: Repeat<TakeDuration<Decoder<BufReader<File>>>>) -> Self {
-        Taal { taal }
+    pub fn new(name: String, vibhags: Vec<Vibhag>) -> Self {
+        Taal { name, vibhags }
+    }
+
+    /// Reads the taal `name` from `config/taals/<name>.yaml`
+    pub fn load(name: &str) -> Result<Taal, String> {
+        let fp = Path::new(CONF_DIR).join(TAALS_DIR).join(format!("{}.yaml", name));
+        let s = std::fs::read_to_string(&fp)
+            .map_err(|_| format!("Taal {} not found, taals available: {}", name, TAALS.join(",")))?;
+        let docs = YamlLoader::load_from_str(&s)
+            .map_err(|e| format!("Invalid taal {}: {}", name, e))?;
+        match docs.first() {
+            Some(doc) => Taal::from_yaml(doc),
+            _ => Err(format!("Taal {} is empty", name)),
+        }
+    }
+
+    /// Reads a taal from yaml, listing its vibhags with their theka, e.g.:
+    ///
+    /// name: dadra
+    /// vibhags:
+    ///   - tali: Dha Dhi Na
+    ///   - khali: Dha Ti Na
+    pub fn from_yaml(doc: &Yaml) -> Result<Taal, String> {
+        let name = doc["name"].as_str().ok_or("Taal has no name")?;
+        let vibhags_yaml = doc["vibhags"].as_vec()
+            .ok_or(format!("Taal {} has no vibhags", name))?;
+
+        let mut vibhags = Vec::<Vibhag>::new();
+        for v in vibhags_yaml {
+            let (clap, bols) = match (v["tali"].as_str(), v["khali"].as_str()) {
+                (Some(bols), None) => (Clap::Tali, bols),
+                (None, Some(bols)) => (Clap::Khali, bols),
+                _ => return Err(format!("Vibhag {} of taal {} is neither tali nor khali",
+                                        vibhags.len() + 1, name)),
+            };
+            let bols: Vec<String> = bols.split_whitespace().map(|b| b.to_string()).collect();
+            if bols.is_empty() {
+                return Err(format!("Vibhag {} of taal {} has no bols", vibhags.len() + 1, name));
+            }
+            vibhags.push(Vibhag::new(clap, bols));
+        }
+        if vibhags.is_empty() {
+            return Err(format!("Taal {} has no vibhags", name));
+        }
+
+        Ok(Taal::new(name.to_string(), vibhags))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn vibhags(&self) -> &Vec<Vibhag> {
+        &self.vibhags
+    }
+
+    /// Returns the number of matras in a cycle
+    pub fn matras(&self) -> usize {
+        self.vibhags.iter().map(|v| v.matras()).sum()
+    }
+
+    /// Returns the matra within the cycle that the `n`th matra (counting on
+    /// from sam across cycles) falls on
+    pub fn matra(&self, n: usize) -> usize {
+        (n.max(1) - 1) % self.matras() + 1
+    }
+
+    /// Returns the number of whole cycles `beats` fill, and the beats left
+    /// over after them
+    pub fn avartans(&self, beats: Beats) -> (usize, Beats) {
+        let matras = self.matras() as i64;
+        let avartans = beats.floor().div_euclid(matras);
+        (avartans.max(0) as usize, beats - Beats::whole(avartans * matras))
+    }
+
+    pub fn is_sam(&self, matra: usize) -> bool {
+        self.matra(matra) == 1
+    }
+
+    /// Returns the index of the vibhag `matra` falls in, and its position
+    /// (from 0) within the vibhag
+    pub fn vibhag_at(&self, matra: usize) -> (usize, usize) {
+        let mut pos = self.matra(matra) - 1;
+        for (i, v) in self.vibhags.iter().enumerate() {
+            if pos < v.matras() {
+                return (i, pos);
+            }
+            pos -= v.matras();
+        }
+
+        (0, 0)
+    }
+
+    /// Returns the clap on `matra`, if it starts a vibhag
+    pub fn clap(&self, matra: usize) -> Option<Clap> {
+        match self.vibhag_at(matra) {
+            (i, 0) => Some(self.vibhags[i].clap),
+            _ => None,
+        }
+    }
+
+    /// Returns the matras with a tali
+    pub fn talis(&self) -> Vec<usize> {
+        self.claps(Clap::Tali)
+    }
+
+    /// Returns the matras with a khali
+    pub fn khalis(&self) -> Vec<usize> {
+        self.claps(Clap::Khali)
+    }
+
+    fn claps(&self, clap: Clap) -> Vec<usize> {
+        (1..=self.matras()).filter(|m| self.clap(*m) == Some(clap)).collect()
+    }
+
+    /// Returns the bols of the theka, one per matra
+    pub fn theka(&self) -> Vec<&str> {
+        self.vibhags.iter()
+            .flat_map(|v| v.bols.iter().map(|b| b.as_str()))
+            .collect()
+    }
+
+    /// Returns the bol of the theka on `matra`
+    pub fn bol(&self, matra: usize) -> &str {
+        let (i, j) = self.vibhag_at(matra);
+        &self.vibhags[i].bols[j]
+    }
+
+    /// Returns how loud a beat on `matra` is: sam is the loudest and a
+    /// khali the softest
+    pub fn accent(&self, matra: usize) -> f32 {
+        match self.clap(matra) {
+            _ if self.is_sam(matra) => SAM_ACCENT,
+            Some(Clap::Tali) => TALI_ACCENT,
+            Some(Clap::Khali) => KHALI_ACCENT,
+            _ => MATRA_ACCENT,
+        }
+    }
+
+    /// Returns the taal played by sounding `beat` on every matra, at
+    /// `sample_rate`
+    pub fn beats<S>(&self, beat: S, sample_rate: u32) -> TaalBeats<S>
+    where
+        S: Source<Item = f32> + Clone,
+    {
+        TaalBeats::new(self.clone(), beat, sample_rate)
+    }
+
+    /// Returns how each vibhag is marked in notation: `X` for sam, `0` for a
+    /// khali and the talis numbered in order, e.g. `X 2 0 3` for teentaal
+    pub fn marks(&self) -> Vec<String> {
+        let mut talis = 0;
+        self.vibhags.iter()
+            .enumerate()
+            .map(|(i, v)| match v.clap {
+                Clap::Khali => "0".to_string(),
+                Clap::Tali => {
+                    talis += 1;
+                    if i == 0 { "X".to_string() } else { talis.to_string() }
+                },
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Taal {
+    /// Shows the theka with the mark of each vibhag, e.g.
+    /// `X Dha Dhi Na | 0 Dha Ti Na`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let vibhags: Vec<String> = self.vibhags.iter()
+            .zip(self.marks())
+            .map(|(v, mark)| format!("{} {}", mark, v.bols.join(" ")))
+            .collect();
+        write!(f, "{}", vibhags.join(" | "))
+    }
+}
+
+/// Sounds a taal a matra at a time, so that it keeps time with a melody
+/// however the melody's tempo changes
+pub trait TaalSound {
+    /// Returns the sound of `matra` (counting on from sam across cycles)
+    /// lasting `len` samples; a stroke may ring on past the end of the matra
+    fn matra(&self, matra: usize, len: usize) -> Box<dyn Iterator<Item = f32> + Send>;
+}
+
+/// A taal's matras, each sounding a beat (cut off if it is longer than the
+/// matra) accented by its place in the taal
+#[derive(Clone)]
+pub struct TaalBeats<S> {
+    taal: Taal,
+    beat: S,
+    sample_rate: u32,
+}
+
+impl<S: Source<Item = f32> + Clone> TaalBeats<S> {
+    pub fn new(taal: Taal, beat: S, sample_rate: u32) -> Self {
+        TaalBeats {
+            taal,
+            beat,
+            sample_rate,
+        }
+    }
+}
+
+impl<S> TaalSound for TaalBeats<S>
+where
+    S: Source<Item = f32> + Clone + Send + 'static,
+{
+    fn matra(&self, matra: usize, len: usize) -> Box<dyn Iterator<Item = f32> + Send> {
+        let accent = self.taal.accent(matra);
+        let beat = UniformSourceIterator::<S, f32>::new(self.beat.clone(), 1, self.sample_rate);
+        Box::new(beat.take(len).map(move |v| v * accent))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yaml_rust::YamlLoader;
+    use rodio::source::SineWave;
+    use crate::raagas::taal::{Taal, TaalSound, Clap, TAALS};
+    use crate::raagas::beats::Beats;
+
+    /// test teentaal's matras, vibhags and theka
+    #[test]
+    fn test_teentaal() {
+        let taal = Taal::load("teentaal").unwrap();
+        assert_eq!(taal.matras(), 16);
+        assert_eq!(taal.talis(), vec![1, 5, 13]);
+        assert_eq!(taal.khalis(), vec![9]);
+        assert_eq!(taal.marks(), vec!["X", "2", "0", "3"]);
+        assert_eq!(taal.bol(10), "Tin");
+        assert_eq!(taal.clap(10), None);
+        assert!(taal.is_sam(17));
+        assert_eq!(taal.vibhag_at(20), (0, 3));
+    }
+
+    /// test a length in beats is counted in whole avartans exactly
+    #[test]
+    fn test_avartans() {
+        let dadra = Taal::load("dadra").unwrap();
+        let third = Beats::new(1, 3);
+        let line: Beats = std::iter::repeat_n(third, 18).sum();
+        assert_eq!(dadra.avartans(line), (1, Beats::ZERO));
+        assert_eq!(dadra.avartans(line + Beats::new(3, 2)), (1, Beats::new(3, 2)));
+        assert_eq!(dadra.avartans(Beats::new(11, 2)), (0, Beats::new(11, 2)));
+    }
+
+    /// test every taal can be loaded, and rupak's sam falls on its khali
+    #[test]
+    fn test_load_taals() {
+        let matras: Vec<usize> = TAALS.iter().map(|t| Taal::load(t).unwrap().matras()).collect();
+        assert_eq!(matras, vec![16, 12, 10, 7, 6, 8]);
+
+        let rupak = Taal::load("rupak").unwrap();
+        assert_eq!(rupak.clap(1), Some(Clap::Khali));
+        assert_eq!(rupak.to_string(), "0 Tin Tin Na | 1 Dhi Na | 2 Dhi Na");
+        assert!(Taal::load("chautaal").is_err());
+    }
+
+    /// test a vibhag must be either tali or khali
+    #[test]
+    fn test_taal_from_invalid_yaml() {
+        let docs = YamlLoader::load_from_str("name: x\nvibhags:\n  - clap: Dha\n").unwrap();
+        assert!(Taal::from_yaml(&docs[0]).is_err());
+        let docs = YamlLoader::load_from_str("name: x\n").unwrap();
+        assert!(Taal::from_yaml(&docs[0]).is_err());
     }
 
-    pub fn play(&self, sink: Sink) {
-        sink.append(self.taal.clone());
+    /// test every matra sounds the beat for its length, with sam accented and
+    /// khali softer
+    #[test]
+    fn test_taal_beats() {
+        let taal = Taal::load("dadra").unwrap();
+        // a 440 Hz sine resampled to 48000 Hz
+        let beats = taal.beats(SineWave::new(440), 48000);
+        let peak = |s: Vec<f32>| s.iter().fold(0.0f32, |m, v| m.max(v.abs()));
+        let khali = peak(beats.matra(4, 480).collect());
+        let matra = peak(beats.matra(5, 480).collect());
+        // the 7th matra is sam again
+        let sam = peak(beats.matra(7, 480).collect());
+        assert!(khali < matra && matra < sam);
+        assert_eq!(beats.matra(2, 240).count(), 240);
     }
 }
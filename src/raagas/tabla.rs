@@ -0,0 +1,208 @@
+use std::f64::consts::PI;
+
+use crate::raagas::taal::{Taal, TaalSound};
+
+// syllables bols are made of, longest first so `dhin` is not read as `dhi`
+// followed by `n`, with the strokes played for each
+const SYLLABLES: [(&str, &[Stroke]); 15] = [
+    ("dhin", &[Stroke::Tin, Stroke::Ge]),
+    ("dha", &[Stroke::Na, Stroke::Ge]),
+    ("dhi", &[Stroke::Tin, Stroke::Ge]),
+    ("ghe", &[Stroke::Ge]),
+    ("kat", &[Stroke::Ka]),
+    ("tin", &[Stroke::Tin]),
+    ("ge", &[Stroke::Ge]),
+    ("ka", &[Stroke::Ka]),
+    ("ke", &[Stroke::Ka]),
+    ("ki", &[Stroke::Ka]),
+    ("na", &[Stroke::Na]),
+    ("ra", &[Stroke::Ti]),
+    ("ta", &[Stroke::Na]),
+    ("ti", &[Stroke::Ti]),
+    ("tu", &[Stroke::Tu]),
+];
+// loudness of a stroke, leaving headroom for the melody when two are struck
+// together
+const GAIN: f64 = 0.4;
+
+/// A single stroke on the tabla: the dayan (right drum) is tuned to Sa, the
+/// bayan (left drum) sounds an octave below it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stroke {
+    /// dayan struck on the rim, ringing brightly
+    Na,
+    /// dayan struck between the rim and the syahi, ringing on
+    Tin,
+    /// dayan struck open on the syahi, mostly its fundamental
+    Tu,
+    /// dayan struck and damped on the syahi
+    Ti,
+    /// bayan struck open, its pitch bending down as it rings
+    Ge,
+    /// bayan slapped flat and damped
+    Ka,
+}
+
+impl Stroke {
+    /// Returns how long the stroke rings for, in seconds
+    pub fn len(&self) -> f64 {
+        match self {
+            Stroke::Na => 0.6,
+            Stroke::Tin => 0.9,
+            Stroke::Tu => 1.0,
+            Stroke::Ti => 0.15,
+            Stroke::Ge => 0.9,
+            Stroke::Ka => 0.1,
+        }
+    }
+
+    /// Returns the stroke `t` seconds after it is struck on a tabla tuned to
+    /// `sa`; `noise` is a random value in [-1, 1] for the thud of the stroke
+    pub fn sample(&self, t: f64, sa: f64, noise: f64) -> f64 {
+        // partials of the drum (as a multiple of its pitch) with their
+        // amplitudes, and how fast (in seconds) the stroke dies down
+        let (freq, partials, decay, thud): (f64, &[(f64, f64)], f64, f64) = match self {
+            Stroke::Na => (sa, &[(1.0, 1.0), (2.0, 0.6), (3.0, 0.4), (4.1, 0.25)], 0.15, 0.2),
+            Stroke::Tin => (sa, &[(1.0, 1.0), (2.0, 0.5), (3.0, 0.2)], 0.3, 0.1),
+            Stroke::Tu => (sa, &[(1.0, 1.0), (2.0, 0.15)], 0.35, 0.1),
+            Stroke::Ti => (sa, &[(1.0, 0.6), (2.7, 0.4)], 0.03, 0.5),
+            Stroke::Ge => {
+                // the pressure of the palm bends the pitch down from a fifth above
+                let bend = 1.0 + 0.5 * (-t / 0.04).exp();
+                (sa / 2.0 * bend, &[(1.0, 1.0), (2.0, 0.3)], 0.3, 0.1)
+            },
+            Stroke::Ka => (sa / 2.0, &[(1.0, 0.3)], 0.02, 1.0),
+        };
+
+        let total: f64 = partials.iter().map(|(_, a)| a).sum();
+        let tone: f64 = partials.iter()
+            // higher partials die down faster
+            .map(|(k, a)| a * (2.0 * PI * k * freq * t).sin() * (-t * k / decay).exp())
+            .sum();
+
+        tone / total * (1.0 - thud) + noise * thud * (-t / 0.01).exp()
+    }
+}
+
+/// Returns the strokes of a bol, e.g. `Dha` or `Tirakita`, split evenly over
+/// its matra: one group of strokes (struck together) per syllable
+pub fn strokes(bol: &str) -> Result<Vec<&'static [Stroke]>, String> {
+    let bol_lower = bol.to_lowercase();
+    let mut rest = bol_lower.as_str();
+    let mut strokes = Vec::new();
+    while !rest.is_empty() {
+        let (syllable, s) = SYLLABLES.iter()
+            .find(|(syllable, _)| rest.starts_with(syllable))
+            .ok_or(format!("Bol {} can't be played on the tabla", bol))?;
+        strokes.push(*s);
+        rest = &rest[syllable.len()..];
+    }
+
+    Ok(strokes)
+}
+
+/// A taal's theka played on the tabla a matra at a time, with the strokes of
+/// each bol spread evenly over its matra and ringing on into the next
+#[derive(Debug, Clone)]
+pub struct Theka {
+    taal: Taal,
+    // strokes of the bol on each matra
+    bols: Vec<Vec<&'static [Stroke]>>,
+    sa: f64,
+    sample_rate: u32,
+}
+
+impl Theka {
+    /// Returns the theka of `taal` on a tabla tuned to `sa`, unless it has
+    /// bols the tabla can't play
+    pub fn new(taal: &Taal, sa: f64, sample_rate: u32) -> Result<Self, String> {
+        let bols = taal.theka()
+            .iter()
+            .map(|bol| strokes(bol))
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(Theka {
+            taal: taal.clone(),
+            bols,
+            sa,
+            sample_rate,
+        })
+    }
+}
+
+impl TaalSound for Theka {
+    fn matra(&self, matra: usize, len: usize) -> Box<dyn Iterator<Item = f32> + Send> {
+        let matra = self.taal.matra(matra);
+        let groups = &self.bols[matra - 1];
+        let accent = 0.6 + 0.4 * self.taal.accent(matra) as f64;
+        let spacing = len / groups.len();
+        let stroke_len = |s: &Stroke| (s.len() * self.sample_rate as f64) as usize;
+        let ring = groups.iter()
+            .enumerate()
+            .flat_map(|(j, group)| group.iter().map(move |s| (j, s)))
+            .map(|(j, s)| j * spacing + stroke_len(s))
+            .max()
+            .unwrap_or(0);
+        let mut samples = vec![0.0f32; ring.max(len)];
+        // a fixed seed for each matra, so the theka sounds the same every cycle
+        let mut seed: u32 = 0x2545_f491 ^ matra as u32;
+        let mut noise = || {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (seed >> 8) as f64 / (1u32 << 23) as f64 - 1.0
+        };
+
+        for (j, group) in groups.iter().enumerate() {
+            for stroke in group.iter() {
+                for n in 0..stroke_len(stroke) {
+                    let t = n as f64 / self.sample_rate as f64;
+                    let v = stroke.sample(t, self.sa, noise()) * accent * GAIN;
+                    samples[j * spacing + n] += v as f32;
+                }
+            }
+        }
+
+        Box::new(samples.into_iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::raagas::tabla::{strokes, Stroke, Theka};
+    use crate::raagas::taal::{Taal, TaalSound, Vibhag, Clap, TAALS};
+
+    /// test bols are split into the strokes of their syllables
+    #[test]
+    fn test_bol_strokes() {
+        assert_eq!(strokes("Dha").unwrap(), vec![&[Stroke::Na, Stroke::Ge][..]]);
+        assert_eq!(strokes("Dhin").unwrap(), vec![&[Stroke::Tin, Stroke::Ge][..]]);
+        assert_eq!(strokes("Dhage").unwrap().len(), 2);
+        let tirakita = strokes("Tirakita").unwrap();
+        assert_eq!(tirakita, vec![&[Stroke::Ti][..], &[Stroke::Ti], &[Stroke::Ka], &[Stroke::Na]]);
+        assert!(strokes("Dhet").is_err());
+    }
+
+    /// test the theka of every taal can be played, with a stroke on every
+    /// matra ringing on past it
+    #[test]
+    fn test_theka() {
+        for t in TAALS.iter() {
+            let taal = Taal::load(t).unwrap();
+            assert!(Theka::new(&taal, 277.18, 8000).is_ok());
+        }
+
+        let taal = Taal::load("teentaal").unwrap();
+        let theka = Theka::new(&taal, 277.18, 8000).unwrap();
+        for m in 1..=16 {
+            let samples: Vec<f32> = theka.matra(m, 800).collect();
+            assert!(samples.len() > 800);
+            assert!(samples.iter().all(|v| v.abs() <= 0.8));
+            assert!(samples[..80].iter().any(|v| v.abs() > 0.05));
+        }
+        // the same a cycle later
+        assert!(theka.matra(9, 800).eq(theka.matra(25, 800)));
+        // Tirakita's strokes are spread over a shorter matra
+        let kt = Taal::new("kt".to_string(), vec![Vibhag::new(Clap::Tali, vec!["Tirakita".to_string()])]);
+        let theka = Theka::new(&kt, 277.18, 8000).unwrap();
+        assert!(theka.matra(1, 400).count() < theka.matra(1, 800).count());
+    }
+}
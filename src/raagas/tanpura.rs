@@ -0,0 +1,287 @@
+use std::f64::consts::PI;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use rodio::Source;
+
+use crate::raagas::constants::{TANPURA_CYCLE, TANPURA_VOL};
+use crate::raagas::pitch::Pitch;
+use crate::raagas::swars::Swar;
+use crate::raagas::sound::Hertz;
+use crate::raagas::tuning::Tuning;
+
+pub const TANPURA_TUNINGS: [&str; 3] = ["pa", "ma", "ni"];
+
+// seconds for a plucked string to die down to about a third
+const DECAY: f64 = 2.0;
+// seconds for a pluck to reach full volume, so it doesn't click
+const PLUCK: f64 = 0.01;
+const HARMONICS: usize = 16;
+
+/// How the tanpura is tuned, named after its first string: Pa–Sa–Sa–Sa,
+/// Ma–Sa–Sa–Sa or Ni–Sa–Sa–Sa.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TanpuraTuning {
+    Pa,
+    Ma,
+    Ni,
+}
+
+impl TanpuraTuning {
+    /// Returns the tuning suited to a raag with `swars`: Pa, unless the raag
+    /// skips it (then Ma, e.g. for Malkauns, else Ni)
+    pub fn for_swars(swars: &[&Swar]) -> Self {
+        let has = |swar: &str| swars.iter().any(|sw| {
+            sw.pitch.as_ref().is_some_and(|p| p.to_string().trim_matches('.') == swar)
+        });
+        if swars.is_empty() || has("P") {
+            TanpuraTuning::Pa
+        } else if has("M") {
+            TanpuraTuning::Ma
+        } else if has("N") {
+            TanpuraTuning::Ni
+        } else {
+            TanpuraTuning::Pa
+        }
+    }
+
+    /// Returns the four strings in the order they are plucked: the first
+    /// string and Sa in the mandra saptak, two Sa in the madhya saptak and the
+    /// kharaj (lower) Sa.
+    pub fn strings(&self) -> [Pitch; 4] {
+        let first = match self {
+            TanpuraTuning::Pa => ".P",
+            TanpuraTuning::Ma => ".M",
+            TanpuraTuning::Ni => ".N",
+        };
+
+        [
+            Pitch::new(first.to_string()),
+            Pitch::new("S".to_string()),
+            Pitch::new("S".to_string()),
+            Pitch::new(".S".to_string()),
+        ]
+    }
+}
+
+impl FromStr for TanpuraTuning {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "pa" | "p" => Ok(TanpuraTuning::Pa),
+            "ma" | "m" => Ok(TanpuraTuning::Ma),
+            "ni" | "n" => Ok(TanpuraTuning::Ni),
+            _ => Err(format!(
+                "Tanpura tuning {} is unsupported, tunings allowed: {}",
+                s, TANPURA_TUNINGS.join(",")
+            )),
+        }
+    }
+}
+
+impl fmt::Display for TanpuraTuning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            TanpuraTuning::Pa => "pa",
+            TanpuraTuning::Ma => "ma",
+            TanpuraTuning::Ni => "ni",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// A tanpura droning under a melody, with a volume of its own. Unless it is
+/// given a tuning, one is chosen to suit the melody.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tanpura {
+    tuning: Option<TanpuraTuning>,
+    vol: f32,
+}
+
+impl Tanpura {
+    pub fn new(vol: f32) -> Self {
+        Tanpura {
+            tuning: None,
+            vol,
+        }
+    }
+
+    /// Returns the tanpura tuned to `tuning`, rather than to suit the melody
+    pub fn with_tuning(mut self, tuning: Option<TanpuraTuning>) -> Self {
+        self.tuning = tuning;
+        self
+    }
+
+    pub fn vol(&self) -> f32 {
+        self.vol
+    }
+
+    /// Returns the tuning the tanpura is played in under a melody of `swars`
+    pub fn tuning(&self, swars: &[&Swar]) -> TanpuraTuning {
+        self.tuning.unwrap_or_else(|| TanpuraTuning::for_swars(swars))
+    }
+
+    /// Returns the endless sound of the tanpura under a melody of `swars`,
+    /// its strings tuned to Sa at `sa` in `tuning`
+    pub fn source(&self, swars: &[&Swar], sa: &Hertz, tuning: &Tuning, sample_rate: u32) -> TanpuraSource {
+        TanpuraSource::new(self.tuning(swars), sa, tuning, sample_rate)
+    }
+}
+
+impl Default for Tanpura {
+    fn default() -> Self {
+        Tanpura::new(TANPURA_VOL)
+    }
+}
+
+/// An endless mono source of the four strings plucked in turn over a
+/// `TANPURA_CYCLE`, each ringing on into the next cycle. The jawari (the
+/// curved bridge) makes a plucked string buzz, which is modelled by a peak in
+/// the harmonics sweeping upwards as the string dies down.
+#[derive(Debug, Clone)]
+pub struct TanpuraSource {
+    freqs: Vec<f64>,
+    phases: Vec<f64>,
+    sample_rate: u32,
+    num_sample: usize,
+}
+
+impl TanpuraSource {
+    pub fn new(strings: TanpuraTuning, sa: &Hertz, tuning: &Tuning, sample_rate: u32) -> Self {
+        let freqs: Vec<f64> = strings.strings()
+            .iter()
+            .filter_map(|p| p.hertz(sa, tuning))
+            .map(|hz| hz.freq())
+            .collect();
+
+        TanpuraSource {
+            phases: vec![0.0; freqs.len()],
+            freqs,
+            sample_rate,
+            num_sample: 0,
+        }
+    }
+
+    // loudness of a string `t` seconds after it was first plucked, summed over
+    // the current pluck and the one before, which is still ringing
+    fn amplitude(t: f64, cycle: f64) -> f64 {
+        let since = t % cycle;
+        let mut amp = (-since / DECAY).exp() * (since / PLUCK).min(1.0);
+        if t >= cycle {
+            amp += (-(since + cycle) / DECAY).exp();
+        }
+
+        amp
+    }
+
+    // sum of the harmonics of a string at `phase`, `since` seconds after it
+    // was plucked, and scaled to stay within [-1, 1]
+    fn string(&self, freq: f64, phase: f64, since: f64) -> f64 {
+        let peak = 2.0 + 10.0 * since / TANPURA_CYCLE as f64;
+        let x = 2.0 * PI * phase;
+        let (mut prev, mut cur, cos) = (0.0, x.sin(), x.cos());
+        let mut value = 0.0;
+        let mut total = 0.0;
+        for k in 1..=HARMONICS {
+            // skip harmonics that can't be represented at this sample rate
+            if k as f64 * freq >= self.sample_rate as f64 / 2.0 {
+                break;
+            }
+            let kf = k as f64;
+            let amp = (0.5 + (-(kf - peak).powi(2) / 4.0).exp()) / kf;
+            value += amp * cur;
+            total += amp;
+            // sin((k + 1)x) from sin(kx) and sin((k - 1)x)
+            let next = 2.0 * cos * cur - prev;
+            prev = cur;
+            cur = next;
+        }
+
+        if total > 0.0 { value / total } else { 0.0 }
+    }
+}
+
+impl Iterator for TanpuraSource {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let t = self.num_sample as f64 / self.sample_rate as f64;
+        self.num_sample = self.num_sample.wrapping_add(1);
+        let cycle = TANPURA_CYCLE as f64;
+        let spacing = cycle / self.freqs.len() as f64;
+        // the loudest a string gets, with the previous pluck still ringing
+        let max_amp = 1.0 + (-cycle / DECAY).exp();
+
+        let mut value = 0.0;
+        for i in 0..self.freqs.len() {
+            let freq = self.freqs[i];
+            let plucked_at = i as f64 * spacing;
+            if t >= plucked_at {
+                let since = (t - plucked_at) % cycle;
+                value += Self::amplitude(t - plucked_at, cycle)
+                    * self.string(freq, self.phases[i], since);
+            }
+            self.phases[i] = (self.phases[i] + freq / self.sample_rate as f64).fract();
+        }
+
+        Some((value / (self.freqs.len() as f64 * max_amp)) as f32)
+    }
+}
+
+impl Source for TanpuraSource {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::raagas::tanpura::{Tanpura, TanpuraTuning};
+    use crate::raagas::swarblock::SwarBlock;
+    use crate::raagas::raag::load;
+    use crate::raagas::constants::{self, TANPURA_CYCLE};
+    use crate::raagas::tuning::Tuning;
+
+    /// test the tuning is chosen from the swars of the raag
+    #[test]
+    fn test_tanpura_tuning_for_raag() {
+        let malkauns = load::load_yaml("malkauns", "comp1").unwrap();
+        assert_eq!(TanpuraTuning::for_swars(&malkauns.swars()), TanpuraTuning::Ma);
+        let yaman = load::load_yaml("yaman", "yaman").unwrap();
+        assert_eq!(TanpuraTuning::for_swars(&yaman.swars()), TanpuraTuning::Pa);
+        let marwa = SwarBlock::from(".N r G M' D N S.").to_swars();
+        let marwa: Vec<_> = marwa.iter().collect();
+        assert_eq!(TanpuraTuning::for_swars(&marwa), TanpuraTuning::Ni);
+        // unless the tanpura is given its own tuning
+        let tanpura = Tanpura::default().with_tuning(Some(TanpuraTuning::Pa));
+        assert_eq!(tanpura.tuning(&malkauns.swars()), TanpuraTuning::Pa);
+        assert_eq!("Ma".parse::<TanpuraTuning>(), Ok(TanpuraTuning::Ma));
+        assert!("sa".parse::<TanpuraTuning>().is_err());
+    }
+
+    /// test the strings are plucked in turn and the sound stays within [-1, 1]
+    #[test]
+    fn test_tanpura_source() {
+        let src = Tanpura::default().source(&[], &constants::tonic(), &Tuning::default(), 8000);
+        let samples: Vec<f32> = src.take((2.0 * TANPURA_CYCLE * 8000.0) as usize).collect();
+        assert!(samples.iter().all(|s| s.abs() <= 1.0));
+        // a pluck, after the first string has died down a while
+        let quarter = (TANPURA_CYCLE / 4.0 * 8000.0) as usize;
+        let peak = |s: &[f32]| s.iter().fold(0.0f32, |m, v| m.max(v.abs()));
+        assert!(peak(&samples[quarter..quarter + 400]) > peak(&samples[quarter - 400..quarter]));
+    }
+}
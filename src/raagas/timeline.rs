@@ -0,0 +1,115 @@
+use std::time::Duration;
+
+use rodio::Source;
+
+/// A source scheduled to start at a sample of the timeline, cut off at
+/// `until` if it would play on longer
+pub struct Event {
+    pub at: usize,
+    pub until: Option<usize>,
+    pub vol: f32,
+    pub src: Box<dyn Iterator<Item = f32> + Send>,
+}
+
+/// A mono source mixing every scheduled event at its exact sample offset,
+/// so a whole melody (with its tanpura and taal) plays through a single sink
+/// without drifting.
+pub struct Timeline {
+    sample_rate: u32,
+    len: usize,
+    // events yet to start, latest first
+    pending: Vec<Event>,
+    active: Vec<Event>,
+    num_sample: usize,
+}
+
+impl Timeline {
+    /// Returns the timeline of `events`, lasting `len` samples
+    pub fn new(mut events: Vec<Event>, len: usize, sample_rate: u32) -> Self {
+        events.sort_by_key(|e| std::cmp::Reverse(e.at));
+        Timeline {
+            sample_rate,
+            len,
+            pending: events,
+            active: Vec::new(),
+            num_sample: 0,
+        }
+    }
+
+    /// Returns the length of the timeline, in samples
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Iterator for Timeline {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.num_sample >= self.len {
+            return None;
+        }
+        while self.pending.last().is_some_and(|e| e.at <= self.num_sample) {
+            let e = self.pending.pop().unwrap();
+            self.active.push(e);
+        }
+
+        let n = self.num_sample;
+        let mut value = 0.0;
+        self.active.retain_mut(|e| {
+            if e.until.is_some_and(|until| n >= until) {
+                return false;
+            }
+            match e.src.next() {
+                Some(v) => {
+                    value += v * e.vol;
+                    true
+                },
+                _ => false,
+            }
+        });
+        self.num_sample += 1;
+
+        Some(value)
+    }
+}
+
+impl Source for Timeline {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs_f64(self.len as f64 / self.sample_rate as f64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::raagas::timeline::{Event, Timeline};
+
+    fn event(at: usize, until: Option<usize>, v: f32) -> Event {
+        Event { at, until, vol: 0.5, src: Box::new(std::iter::repeat_n(v, 4)) }
+    }
+
+    /// test events are mixed at their offsets, and cut off at their end
+    #[test]
+    fn test_timeline() {
+        let events = vec![event(3, None, 2.0), event(0, None, 1.0), event(1, Some(2), 4.0)];
+        let timeline = Timeline::new(events, 8, 8000);
+        let samples: Vec<f32> = timeline.collect();
+        assert_eq!(samples, vec![0.5, 2.5, 0.5, 1.5, 1.0, 1.0, 1.0, 0.0]);
+    }
+}
@@ -0,0 +1,234 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::raagas::constants;
+use crate::raagas::pitch::{Degree, Pitch, Variant};
+
+/// The 22 shrutis of a saptak, named after the swar they are a variant of,
+/// with their ratio to Sa.
+pub const SHRUTIS: [(&str, u32, u32); 22] = [
+    ("sa", 1, 1),
+    ("ati-komal re", 256, 243),
+    ("komal re", 16, 15),
+    ("madhya re", 10, 9),
+    ("shuddh re", 9, 8),
+    ("ati-komal ga", 32, 27),
+    ("komal ga", 6, 5),
+    ("shuddh ga", 5, 4),
+    ("tivra ga", 81, 64),
+    ("shuddh ma", 4, 3),
+    ("ekashruti ma", 27, 20),
+    ("tivra ma", 45, 32),
+    ("tivratar ma", 729, 512),
+    ("pa", 3, 2),
+    ("ati-komal dha", 128, 81),
+    ("komal dha", 8, 5),
+    ("shuddh dha", 5, 3),
+    ("tivra dha", 27, 16),
+    ("ati-komal ni", 16, 9),
+    ("komal ni", 9, 5),
+    ("shuddh ni", 15, 8),
+    ("tivra ni", 243, 128),
+];
+
+// 5-limit just intonation ratios, in the same order as `constants::SAPTAK`
+const JUST_RATIOS: [(u32, u32); 12] = [
+    (1, 1), (16, 15), (9, 8), (6, 5), (5, 4), (4, 3),
+    (45, 32), (3, 2), (8, 5), (5, 3), (16, 9), (15, 8)
+];
+
+// the shruti (index in `SHRUTIS`) each swar of `constants::SAPTAK` sits on by default
+const DEFAULT_SHRUTIS: [usize; 12] = [0, 2, 4, 6, 7, 9, 11, 13, 15, 16, 19, 20];
+
+/// How the swars of a saptak are tuned relative to Sa
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TuningSystem {
+    #[default]
+    EqualTemperament,
+    JustIntonation,
+    Shruti,
+}
+
+impl TuningSystem {
+    /// Returns the ratio to Sa of the swar `degree` of the `variant`, e.g.
+    /// tivra Ma; `None` if the swar has no such variant
+    pub fn ratio(&self, degree: Degree, variant: Variant) -> Option<f64> {
+        let i = saptak_index(degree, variant)?;
+        let ratio = match self {
+            TuningSystem::EqualTemperament => {
+                let (_, semitones) = constants::SAPTAK[i];
+                2f64.powf(semitones as f64 / 12.0)
+            },
+            TuningSystem::JustIntonation => {
+                let (num, den) = JUST_RATIOS[i];
+                num as f64 / den as f64
+            },
+            TuningSystem::Shruti => shruti_ratio(DEFAULT_SHRUTIS[i]),
+        };
+
+        Some(ratio)
+    }
+}
+
+impl FromStr for TuningSystem {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "equal" | "et" => Ok(TuningSystem::EqualTemperament),
+            "just" | "ji" => Ok(TuningSystem::JustIntonation),
+            "shruti" | "22-shruti" => Ok(TuningSystem::Shruti),
+            _ => Err(format!("Tuning {} is unsupported, tunings allowed: equal,just,shruti", s)),
+        }
+    }
+}
+
+impl fmt::Display for TuningSystem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            TuningSystem::EqualTemperament => "equal",
+            TuningSystem::JustIntonation => "just",
+            TuningSystem::Shruti => "shruti",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+// the index in `constants::SAPTAK` of the swar `degree` of the `variant`
+fn saptak_index(degree: Degree, variant: Variant) -> Option<usize> {
+    Pitch::of(degree, variant, 0).ok().map(|p| p.semitones() as usize)
+}
+
+fn shruti_ratio(i: usize) -> f64 {
+    let (_, num, den) = SHRUTIS[i];
+    num as f64 / den as f64
+}
+
+/// Returns the index of a shruti, given either by name (`ati-komal re`) or
+/// by its number (0 to 21).
+pub fn shruti_index(shruti: &str) -> Result<usize, String> {
+    let shruti = shruti.trim().to_lowercase();
+    if let Ok(i) = shruti.parse::<usize>() {
+        if i < SHRUTIS.len() {
+            return Ok(i);
+        }
+    }
+
+    SHRUTIS.iter()
+        .position(|(name, _, _)| *name == shruti)
+        .ok_or(format!("Unknown shruti {}", shruti))
+}
+
+/// A tuning system along with swars pinned to specific shrutis
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Tuning {
+    system: TuningSystem,
+    shrutis: BTreeMap<(Degree, Variant), usize>,
+}
+
+impl Tuning {
+    pub fn new(system: TuningSystem) -> Self {
+        Tuning {
+            system,
+            shrutis: BTreeMap::new(),
+        }
+    }
+
+    pub fn system(&self) -> TuningSystem {
+        self.system
+    }
+
+    /// Plays the swar `degree` of the `variant` at the shruti `shruti`
+    /// (index in `SHRUTIS`)
+    pub fn set_shruti(&mut self, degree: Degree, variant: Variant, shruti: usize) {
+        self.shrutis.insert((degree, variant), shruti);
+    }
+
+    /// Returns the ratio to Sa of the swar `degree` of the `variant`,
+    /// honouring any shruti it is pinned to
+    pub fn ratio(&self, degree: Degree, variant: Variant) -> Option<f64> {
+        match self.shrutis.get(&(degree, variant)) {
+            Some(i) => Some(shruti_ratio(*i)),
+            _ => self.system.ratio(degree, variant),
+        }
+    }
+
+    /// Returns this tuning as adjusted by a raag
+    pub fn overridden_by(&self, o: &TuningOverride) -> Tuning {
+        let mut tuning = self.clone();
+        if let Some(system) = o.system {
+            tuning.system = system;
+        }
+        for ((degree, variant), i) in &o.shrutis {
+            tuning.set_shruti(*degree, *variant, *i);
+        }
+
+        tuning
+    }
+}
+
+/// A raag's adjustments to the tuning it is played in, e.g. an ati-komal
+/// re for Bhairav.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TuningOverride {
+    pub system: Option<TuningSystem>,
+    pub shrutis: BTreeMap<(Degree, Variant), usize>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::raagas::tuning::{Tuning, TuningSystem, TuningOverride, shruti_index};
+    use crate::raagas::constants::{initialise_swars, SA};
+    use crate::raagas::sound::Hertz;
+    use crate::raagas::pitch::{Degree, Pitch, Variant};
+
+    /// test the ratios of Pa and tivra Ma in each tuning system
+    #[test]
+    fn test_tuning_system_ratio() {
+        let et = TuningSystem::EqualTemperament;
+        let ji = TuningSystem::JustIntonation;
+        let shruti = TuningSystem::Shruti;
+        assert!((et.ratio(Degree::Pa, Variant::Shuddh).unwrap() - 1.4983).abs() < 0.0001);
+        assert_eq!(ji.ratio(Degree::Pa, Variant::Shuddh).unwrap(), 1.5);
+        assert_eq!(ji.ratio(Degree::Ma, Variant::Tivra).unwrap(), 45.0 / 32.0);
+        assert_eq!(shruti.ratio(Degree::Ni, Variant::Komal).unwrap(), 9.0 / 5.0);
+        assert!(ji.ratio(Degree::Pa, Variant::Tivra).is_none());
+    }
+
+    /// test a swar pinned to a shruti overrides the tuning system
+    #[test]
+    fn test_tuning_with_shruti() {
+        let mut tuning = Tuning::new(TuningSystem::Shruti);
+        assert_eq!(tuning.ratio(Degree::Re, Variant::Komal).unwrap(), 16.0 / 15.0);
+        tuning.set_shruti(Degree::Re, Variant::Komal, shruti_index("ati-komal re").unwrap());
+        assert_eq!(tuning.ratio(Degree::Re, Variant::Komal).unwrap(), 256.0 / 243.0);
+        assert_eq!(shruti_index("21"), Ok(21));
+        assert!(shruti_index("22").is_err());
+    }
+
+    /// test a raag's tuning overrides the system and pins swars
+    #[test]
+    fn test_tuning_overridden_by_raag() {
+        let mut o = TuningOverride {
+            system: Some(TuningSystem::JustIntonation),
+            ..Default::default()
+        };
+        o.shrutis.insert((Degree::Dha, Variant::Komal), 14);
+        let tuning = Tuning::default().overridden_by(&o);
+        assert_eq!(tuning.system(), TuningSystem::JustIntonation);
+        assert_eq!(tuning.ratio(Degree::Dha, Variant::Komal).unwrap(), 128.0 / 81.0);
+        assert_eq!(tuning.ratio(Degree::Dha, Variant::Shuddh).unwrap(), 5.0 / 3.0);
+    }
+
+    /// test frequencies and tones of swars in just intonation
+    #[test]
+    fn test_swars_in_just_intonation() {
+        let sa = SA.parse::<Hertz>().unwrap();
+        let swars = initialise_swars(&sa, &Tuning::new(TuningSystem::JustIntonation));
+        assert_eq!(swars.get(&Pitch::new("S".to_string())).unwrap(), &Hertz::new(277.18, "C#".to_string()));
+        assert_eq!(swars.get(&Pitch::new("P".to_string())).unwrap(), &Hertz::new(415.77, "G#".to_string()));
+        assert_eq!(swars.get(&Pitch::new("P.".to_string())).unwrap(), &Hertz::new(831.55, "G#".to_string()));
+        assert_eq!(swars.get(&Pitch::new(".P".to_string())).unwrap(), &Hertz::new(207.89, "G#".to_string()));
+    }
+}
@@ -1,7 +1,7 @@
 use std::io::BufRead;
 use std::thread::sleep;
 use std::time::Duration;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use crate::raagas::constants::CONF_DIR;
 
 pub(crate) fn lines_from_file(fp: String) -> Vec<String> {
@@ -14,14 +14,9 @@ pub(crate) fn lines_from_file(fp: String) -> Vec<String> {
     lines
 }
 
-pub(crate) fn read_composition_as_str(raag: &str, composition: &str) -> String {
-    let comp_path = format!("{}.yaml", composition);
-    let raagp = Path::new(CONF_DIR).join(raag).join(comp_path);
-    let f = std::fs::read_to_string(raagp);
-    match &f {
-        Ok(_s) => String::from(_s),
-        _ => String::new(),
-    }
+/// Returns the path of the yaml file `composition` of `raag` is read from
+pub(crate) fn composition_path(raag: &str, composition: &str) -> PathBuf {
+    Path::new(CONF_DIR).join(raag).join(format!("{}.yaml", composition))
 }
 
 pub fn delay(t: f32) {